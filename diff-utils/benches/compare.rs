@@ -0,0 +1,25 @@
+//! Benchmarks of comparisons on representative corpora:
+//! ```bash
+//! cargo bench -p diff_utils --features perf-test --bench compare
+//! ```
+//! Optional argument filters corpora by name. Time budgets of the same corpora are checked by
+//! the `perf` test instead.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use diff_utils::{perf, Comparison};
+
+fn compare(c: &mut Criterion) {
+    let mut group = c.benchmark_group("compare");
+    group.sample_size(10);
+    for corpus in perf::corpora() {
+        let left = corpus.left.iter().map(String::as_str).collect::<Vec<_>>();
+        let right = corpus.right.iter().map(String::as_str).collect::<Vec<_>>();
+        group.bench_function(corpus.name, |b| {
+            b.iter(|| Comparison::new(&left, &right).compare_infallible())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, compare);
+criterion_main!(benches);
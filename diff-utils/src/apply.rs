@@ -0,0 +1,59 @@
+use crate::{Hunk, LineKind};
+
+/// Which file is rebuilt from the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Direction {
+    /// Right from left
+    Forward,
+    /// Left from right
+    Backward,
+}
+
+/// Applies hunks on the `text`, returning `None` if they don't match it.
+pub(crate) fn apply<'b>(
+    hunks: &[Hunk<'b>],
+    text: &[&'b str],
+    direction: Direction,
+) -> Option<Vec<&'b str>> {
+    let mut result = Vec::with_capacity(text.len());
+    let mut cursor = 0;
+    for hunk in hunks {
+        let (start, result_start) = match direction {
+            Direction::Forward => (hunk.old_start, hunk.new_start),
+            Direction::Backward => (hunk.new_start, hunk.old_start),
+        };
+        result.extend_from_slice(text.get(cursor..start)?);
+        cursor = start;
+        if result.len() != result_start {
+            return None;
+        }
+
+        for line in &hunk.lines {
+            let kind = match direction {
+                Direction::Forward => line.kind,
+                Direction::Backward => line.kind.invert(),
+            };
+            match kind {
+                LineKind::Unchanged => {
+                    let old = *text.get(cursor)?;
+                    if old != line.inner {
+                        return None;
+                    }
+                    result.push(old);
+                    cursor += 1;
+                }
+                LineKind::Removed | LineKind::ReplaceRemoved => {
+                    if *text.get(cursor)? != line.inner {
+                        return None;
+                    }
+                    cursor += 1;
+                }
+                LineKind::Inserted | LineKind::ReplaceInserted => {
+                    result.push(line.inner);
+                }
+            }
+        }
+    }
+    result.extend_from_slice(text.get(cursor..)?);
+    Some(result)
+}
@@ -0,0 +1,198 @@
+use crate::{CompareResult, Hunk};
+use std::fmt;
+
+/// Kind of change a hunk contains, assigned by a [`Classifier`](trait.Classifier.html).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum HunkClass {
+    /// Lines differ only in whitespace, e.g. indentation or line wrapping
+    Whitespace,
+    /// Only comments changed
+    Comment,
+    /// The same lines, just in different order
+    Reorder,
+    /// Anything else. Used when no classifier recognizes the hunk
+    Content,
+}
+
+/// Recognizes a specific kind of change, e.g. to auto-approve formatting-only drift.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::{Classifier, Comparison, Hunk, HunkClass, DEFAULT_CLASSIFIERS};
+///
+/// #[derive(Debug)]
+/// struct Blank;
+///
+/// impl Classifier for Blank {
+///     fn classify(&self, hunk: &Hunk<'_>) -> Option<HunkClass> {
+///         let blank = hunk.lines().iter().all(|l| !l.kind().is_change() || l.inner().is_empty());
+///         Some(HunkClass::Whitespace).filter(|_| blank)
+///     }
+/// }
+///
+/// let result = Comparison::new(&["foo"], &["foo", ""]).compare_infallible();
+/// assert_eq!(result.classify(&[&Blank]), vec![HunkClass::Whitespace]);
+/// assert_eq!(result.classify(DEFAULT_CLASSIFIERS), vec![HunkClass::Whitespace]);
+/// ```
+pub trait Classifier: fmt::Debug + Sync {
+    /// Returns the class of the hunk, or `None` if it is not recognized
+    fn classify(&self, hunk: &Hunk<'_>) -> Option<HunkClass>;
+}
+
+/// Recognizes [`HunkClass::Whitespace`](enum.HunkClass.html#variant.Whitespace): both sides
+/// are the same after removing all whitespace.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WhitespaceOnly;
+
+impl Classifier for WhitespaceOnly {
+    fn classify(&self, hunk: &Hunk<'_>) -> Option<HunkClass> {
+        let side = |removed: bool| {
+            changed(hunk, removed)
+                .flat_map(str::chars)
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+        };
+        Some(HunkClass::Whitespace).filter(|_| side(true) == side(false))
+    }
+}
+
+const COMMENT_PREFIXES: &[&str] = &["//", "#", "--", ";"];
+
+/// Recognizes [`HunkClass::Comment`](enum.HunkClass.html#variant.Comment): every changed line
+/// is blank or starts with one of the comment prefixes, after leading whitespace. By default
+/// these are `//`, `#`, `--` and `;`.
+#[derive(Debug, Clone, Copy)]
+pub struct CommentOnly(pub &'static [&'static str]);
+
+impl Default for CommentOnly {
+    fn default() -> Self {
+        Self(COMMENT_PREFIXES)
+    }
+}
+
+impl Classifier for CommentOnly {
+    fn classify(&self, hunk: &Hunk<'_>) -> Option<HunkClass> {
+        let comment = |line: &str| {
+            let line = line.trim_start();
+            line.is_empty() || self.0.iter().any(|prefix| line.starts_with(prefix))
+        };
+        let only_comments = changed(hunk, true).chain(changed(hunk, false)).all(comment);
+        Some(HunkClass::Comment).filter(|_| only_comments)
+    }
+}
+
+/// Recognizes [`HunkClass::Reorder`](enum.HunkClass.html#variant.Reorder): removed and inserted
+/// lines are the same, counting repetitions, but in different order.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Reorder;
+
+impl Classifier for Reorder {
+    fn classify(&self, hunk: &Hunk<'_>) -> Option<HunkClass> {
+        let side = |removed: bool| {
+            let mut lines = changed(hunk, removed).collect::<Vec<_>>();
+            lines.sort_unstable();
+            lines
+        };
+        let removed = side(true);
+        Some(HunkClass::Reorder).filter(|_| !removed.is_empty() && removed == side(false))
+    }
+}
+
+/// Built-in classifiers, in order they are tried.
+pub const DEFAULT_CLASSIFIERS: &[&dyn Classifier] =
+    &[&WhitespaceOnly, &Reorder, &CommentOnly(COMMENT_PREFIXES)];
+
+fn changed<'h>(hunk: &'h Hunk<'_>, removed: bool) -> impl Iterator<Item = &'h str> {
+    hunk.lines()
+        .iter()
+        .filter(move |line| match removed {
+            true => line.kind().is_deletion(),
+            false => line.kind().is_insertion(),
+        })
+        .map(|line| line.inner())
+}
+
+impl<'a> Hunk<'a> {
+    /// Class given by the first classifier which recognizes the hunk, or
+    /// [`HunkClass::Content`](enum.HunkClass.html#variant.Content) if none does.
+    pub fn classify(&self, classifiers: &[&dyn Classifier]) -> HunkClass {
+        classifiers
+            .iter()
+            .find_map(|classifier| classifier.classify(self))
+            .unwrap_or(HunkClass::Content)
+    }
+}
+
+impl<'a> CompareResult<'a> {
+    /// Classes of all the hunks, see [`Hunk::classify`](struct.Hunk.html#method.classify).
+    pub fn classify(&self, classifiers: &[&dyn Classifier]) -> Vec<HunkClass> {
+        self.hunks
+            .iter()
+            .map(|hunk| hunk.classify(classifiers))
+            .collect()
+    }
+
+    /// Keeps only hunks for which `keep` returns `true`, e.g. to drop formatting-only changes:
+    /// ```rust
+    /// use diff_utils::{Comparison, HunkClass, DEFAULT_CLASSIFIERS};
+    ///
+    /// let left = ["fn main() {", "    foo();", "}", "", "", "", "", "", "", "bar();"];
+    /// let right = ["fn main() {", "  foo();", "}", "", "", "", "", "", "", "baz();"];
+    /// let mut result = Comparison::new(&left, &right).compare_infallible();
+    /// result.retain_hunks(|hunk| hunk.classify(DEFAULT_CLASSIFIERS) != HunkClass::Whitespace);
+    /// assert_eq!(result.hunks().len(), 1);
+    /// assert_eq!(result.hunks()[0].old_start(), 6);
+    /// ```
+    pub fn retain_hunks(&mut self, keep: impl FnMut(&Hunk<'a>) -> bool) {
+        self.hunks.retain(keep);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Comparison;
+
+    fn classify(left: &[&str], right: &[&str]) -> Vec<HunkClass> {
+        Comparison::new(left, right)
+            .compare_infallible()
+            .classify(DEFAULT_CLASSIFIERS)
+    }
+
+    #[test]
+    fn built_in() {
+        assert_eq!(
+            classify(&["a b", "c"], &["a  b", " c"]),
+            [HunkClass::Whitespace]
+        );
+        assert_eq!(
+            classify(&["a", "b c"], &["a", "b", "c"]),
+            [HunkClass::Whitespace]
+        );
+        assert_eq!(
+            classify(&["x", "y", "z"], &["z", "y", "x"]),
+            [HunkClass::Reorder]
+        );
+        assert_eq!(
+            classify(&["a", "// old"], &["a", "  # new", ""]),
+            [HunkClass::Comment]
+        );
+        assert_eq!(classify(&["a", "b"], &["a", "c"]), [HunkClass::Content]);
+        assert_eq!(
+            classify(&["a", "b"], &["a", "b", "b"]),
+            [HunkClass::Content]
+        );
+        assert!(classify(&["a"], &["a"]).is_empty());
+    }
+
+    #[test]
+    fn first_match_wins() {
+        let result = Comparison::new(&["# a"], &["#a"]).compare_infallible();
+        assert_eq!(
+            result.classify(&[&CommentOnly::default(), &WhitespaceOnly]),
+            [HunkClass::Comment]
+        );
+        assert_eq!(result.classify(&[&Reorder]), [HunkClass::Content]);
+    }
+}
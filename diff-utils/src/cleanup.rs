@@ -0,0 +1,141 @@
+//! Semantic cleanup of the edit script.
+//!
+//! A block of inserted or removed lines surrounded by unchanged ones can often be shifted up or
+//! down without changing the meaning of the diff, e.g. when the block starts and ends with the same
+//! line. Patience algorithm picks any of these positions, which sometimes splits functions or
+//! config sections in a weird way. Here we slide each such block to the position that aligns best
+//! with blank lines and indentation.
+//!
+//! Ties are always broken the same way: of equally good positions the earliest one wins, so the
+//! result doesn't depend on where the diff algorithm happened to put the block.
+
+use crate::ops::Op;
+use std::cmp::Reverse;
+
+/// Slides blocks surrounded by unchanged lines to the position with the best
+/// [`score`](fn.score.html), or the earliest one of equally good positions.
+pub(crate) fn semantic(ops: Vec<Op>, left: &[&str], right: &[&str]) -> Vec<Op> {
+    slide(ops, left, right, score)
+}
+
+/// Slides every block of inserted or removed items as early as possible, also at the edges of
+/// the input. Used to align refined lines, where all the positions are equally good.
+#[cfg(feature = "display-plain")]
+pub(crate) fn earliest(mut ops: Vec<Op>, left: &[&str], right: &[&str]) -> Vec<Op> {
+    let (old, new) = match ops.last() {
+        Some(Op::Equal { old, new, len }) => (old + len, new + len),
+        Some(Op::Delete { old, len, new }) => (old + len, *new),
+        Some(Op::Insert { old, new, len }) => (*old, new + len),
+        Some(Op::Replace {
+            old,
+            old_len,
+            new,
+            new_len,
+        }) => (old + old_len, new + new_len),
+        None => return ops,
+    };
+    // Empty unchanged blocks at both ends let the edge blocks slide too.
+    ops.insert(
+        0,
+        Op::Equal {
+            old: 0,
+            new: 0,
+            len: 0,
+        },
+    );
+    ops.push(Op::Equal { old, new, len: 0 });
+    slide(ops, left, right, |_, _, _| 0)
+}
+
+fn slide(
+    mut ops: Vec<Op>,
+    left: &[&str],
+    right: &[&str],
+    score: impl Fn(&[&str], usize, usize) -> isize,
+) -> Vec<Op> {
+    for i in 1..ops.len().saturating_sub(1) {
+        let (before, after) = match (ops[i - 1], ops[i + 1]) {
+            (Op::Equal { len: before, .. }, Op::Equal { len: after, .. }) => (before, after),
+            _ => continue,
+        };
+        let (text, start, len) = match ops[i] {
+            Op::Delete { old, len, .. } => (left, old, len),
+            Op::Insert { new, len, .. } => (right, new, len),
+            _ => continue,
+        };
+        if len == 0 {
+            continue;
+        }
+
+        let up = (1..=before)
+            .take_while(|k| text[start - k] == text[start + len - k])
+            .count();
+        let down = (0..after)
+            .take_while(|k| text[start + k] == text[start + len + k])
+            .count();
+
+        let best = (0..=up + down)
+            .map(|k| start - up + k)
+            .max_by_key(|&pos| (score(text, pos, len), Reverse(pos)))
+            .unwrap_or(start);
+
+        if best != start {
+            shift(&mut ops, i, best as isize - start as isize);
+        }
+    }
+    ops.retain(|op| !op.is_empty());
+    ops
+}
+
+/// The higher, the more natural is the block `text[start..start + len]` as a hunk.
+fn score(text: &[&str], start: usize, len: usize) -> isize {
+    let end = start + len;
+    let block = &text[start..end];
+    let mut score = 0;
+
+    if start == 0 || is_blank(text[start - 1]) {
+        score += 2;
+    }
+    if end == text.len() || is_blank(text[end - 1]) {
+        score += 1;
+    }
+    if is_blank(block[0]) {
+        score -= 1;
+    } else if block
+        .iter()
+        .filter(|line| !is_blank(line))
+        .all(|line| indent(line) >= indent(block[0]))
+    {
+        score += 1;
+    }
+    score
+}
+
+/// Moves the block `ops[i]` by `by` lines, adjusting neighbouring unchanged blocks.
+fn shift(ops: &mut [Op], i: usize, by: isize) {
+    let moved = |pos: usize| (pos as isize + by) as usize;
+
+    if let Op::Equal { len, .. } = &mut ops[i - 1] {
+        *len = moved(*len);
+    }
+    match &mut ops[i] {
+        Op::Delete { old, new, .. } | Op::Insert { old, new, .. } => {
+            *old = moved(*old);
+            *new = moved(*new);
+        }
+        _ => unreachable!("Only insertions and deletions are shifted"),
+    }
+    if let Op::Equal { old, new, len } = &mut ops[i + 1] {
+        *old = moved(*old);
+        *new = moved(*new);
+        *len = (*len as isize - by) as usize;
+    }
+}
+
+fn is_blank(line: &str) -> bool {
+    line.trim().is_empty()
+}
+
+fn indent(line: &str) -> usize {
+    line.chars().take_while(|c| c.is_whitespace()).count()
+}
@@ -0,0 +1,138 @@
+/*
+
+Here is code for lines considered equal despite whitespace differences
+
+*/
+use std::borrow::Cow;
+
+/// Which differences in whitespace [`Comparison`](struct.Comparison.html) ignores.
+/// Lines are still reported as they are, only their equality is relaxed, e.g. to suppress
+/// differences caused by editors or CI platforms.
+///
+/// Lines considered equal are reported as unchanged with the text of the left side, so
+/// [`CompareResult::verify`](struct.CompareResult.html#method.verify) fails if they differ.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::{CompareOptions, Comparison};
+/// let comparison = Comparison {
+///     options: CompareOptions::default().ignore_trailing_whitespace(),
+///     ..Comparison::new(&["foo  ", "bar"], &["foo", "bar\t"])
+/// };
+/// assert!(comparison.are_equal());
+/// assert!(comparison.compare_infallible().is_empty());
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct CompareOptions {
+    /// Whitespace at the end of lines doesn't matter. Default: false
+    pub ignore_trailing_whitespace: bool,
+    /// Whitespace at the beginning of lines, i.e. indentation, doesn't matter. Default: false
+    pub ignore_leading_whitespace: bool,
+    /// Every run of whitespace inside of lines is the same as a single space. Default: false
+    pub collapse_inner_whitespace: bool,
+}
+
+impl CompareOptions {
+    /// Sets [`ignore_trailing_whitespace`](#structfield.ignore_trailing_whitespace)
+    pub fn ignore_trailing_whitespace(mut self) -> Self {
+        self.ignore_trailing_whitespace = true;
+        self
+    }
+
+    /// Sets [`ignore_leading_whitespace`](#structfield.ignore_leading_whitespace)
+    pub fn ignore_leading_whitespace(mut self) -> Self {
+        self.ignore_leading_whitespace = true;
+        self
+    }
+
+    /// Sets [`collapse_inner_whitespace`](#structfield.collapse_inner_whitespace)
+    pub fn collapse_inner_whitespace(mut self) -> Self {
+        self.collapse_inner_whitespace = true;
+        self
+    }
+
+    /// Checks if lines are compared as they are.
+    pub(crate) fn is_exact(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Form of the line compared instead of it. Borrowed unless inner whitespace is collapsed.
+    pub(crate) fn normalize<'a>(&self, line: &'a str) -> Cow<'a, str> {
+        let mut line = line;
+        if self.ignore_leading_whitespace {
+            line = line.trim_start();
+        }
+        if self.ignore_trailing_whitespace {
+            line = line.trim_end();
+        }
+        if !self.collapse_inner_whitespace || !has_whitespace_run(line) {
+            return Cow::Borrowed(line);
+        }
+        let mut collapsed = String::with_capacity(line.len());
+        let mut in_run = false;
+        for c in line.chars() {
+            match c.is_whitespace() {
+                true if in_run => {}
+                true => {
+                    in_run = true;
+                    collapsed.push(' ');
+                }
+                false => {
+                    in_run = false;
+                    collapsed.push(c);
+                }
+            }
+        }
+        Cow::Owned(collapsed)
+    }
+
+    /// Normalized forms of all the lines.
+    pub(crate) fn normalize_all<'a>(&self, lines: &[&'a str]) -> Vec<Cow<'a, str>> {
+        lines.iter().map(|line| self.normalize(line)).collect()
+    }
+
+    pub(crate) fn eq(&self, left: &str, right: &str) -> bool {
+        left == right || (!self.is_exact() && self.normalize(left) == self.normalize(right))
+    }
+}
+
+/// Checks for whitespace other than a single space, which is already collapsed.
+fn has_whitespace_run(line: &str) -> bool {
+    let mut previous = false;
+    line.chars().any(|c| {
+        let run = c.is_whitespace() && (previous || c != ' ');
+        previous = c.is_whitespace();
+        run
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        let all = CompareOptions::default()
+            .ignore_leading_whitespace()
+            .ignore_trailing_whitespace()
+            .collapse_inner_whitespace();
+        assert_eq!(all.normalize("  foo \t bar\t"), "foo bar");
+        assert!(matches!(
+            all.normalize(" foo bar "),
+            Cow::Borrowed("foo bar")
+        ));
+
+        let inner = CompareOptions::default().collapse_inner_whitespace();
+        assert_eq!(inner.normalize("\tfoo  bar "), " foo bar ");
+        assert_eq!(CompareOptions::default().normalize(" foo "), " foo ");
+    }
+
+    #[test]
+    fn eq() {
+        let trailing = CompareOptions::default().ignore_trailing_whitespace();
+        assert!(trailing.eq("foo ", "foo"));
+        assert!(!trailing.eq(" foo", "foo"));
+        assert!(!CompareOptions::default().eq("foo ", "foo"));
+    }
+}
@@ -1,34 +1,59 @@
 //! Contains [`Context`](struct.Context.html)
 
-use crate::{Hunk, Line};
-use std::collections::VecDeque;
+use crate::{Hunk, Line, LineKind};
 
-#[derive(Debug, Default)]
+/// Hunk which is being built.
+#[derive(Debug)]
 pub(crate) struct Context<'a> {
-    pub start: Option<usize>,
-    pub data: VecDeque<Line<'a>>,
-    pub changed: bool,
+    pub old_start: usize,
+    pub new_start: usize,
+    pub data: Vec<Line<'a>>,
 
-    pub equaled: usize,
     pub removed: usize,
     pub inserted: usize,
+
+    pub last_lines: (Option<usize>, Option<usize>),
 }
 
 impl<'a> Context<'a> {
-    pub fn create_hunk(&mut self, removed: usize, inserted: usize) -> Option<Hunk<'a>> {
-        let start = self.start?;
-        if self.changed {
-            let mut data = VecDeque::new();
-            data.append(&mut self.data);
-            Some(Hunk {
-                old_start: start,
-                removed: self.equaled + self.removed,
-                new_start: start + inserted - removed,
-                inserted: self.equaled + self.inserted,
-                lines: data.into_iter().collect(),
-            })
-        } else {
-            None
+    /// Empty hunk growing with every line, for hunks of unknown size.
+    #[cfg(feature = "patch")]
+    pub fn new(old_start: usize, new_start: usize) -> Self {
+        Self::with_capacity(old_start, new_start, 0)
+    }
+
+    /// Hunk with room for `capacity` lines, allocated at once.
+    pub fn with_capacity(old_start: usize, new_start: usize, capacity: usize) -> Self {
+        Self {
+            old_start,
+            new_start,
+            data: Vec::with_capacity(capacity),
+            removed: 0,
+            inserted: 0,
+            last_lines: (None, None),
+        }
+    }
+
+    pub fn push(&mut self, line: Line<'a>) {
+        match line.kind {
+            LineKind::Removed | LineKind::ReplaceRemoved => self.removed += 1,
+            LineKind::Inserted | LineKind::ReplaceInserted => self.inserted += 1,
+            LineKind::Unchanged => {
+                self.removed += 1;
+                self.inserted += 1;
+            }
+        }
+        self.data.push(line);
+    }
+
+    pub fn create_hunk(self) -> Hunk<'a> {
+        Hunk {
+            old_start: self.old_start,
+            removed: self.removed,
+            new_start: self.new_start,
+            inserted: self.inserted,
+            lines: self.data,
+            last_lines: self.last_lines,
         }
     }
 }
@@ -1,18 +1,106 @@
-use crate::{CompareResult, DisplayOptions};
+use crate::display::hunk::{collapsed_marker, rows, Row};
+use crate::display::line::notes;
+use crate::display::unicode::has_rtl;
+use crate::{CompareResult, DisplayOptions, Hunk, LineKind};
 use itertools::Itertools;
 use std::fmt;
+use std::slice::Chunks;
 
 impl<'a> CompareResult<'a> {
-    /// Returns a structure which implements [`Display`](std::fmt::Display) with ANSI escape color codes.
+    /// Returns a structure which implements [`Display`](std::fmt::Display), styled by the painter
+    /// from `options`.
     pub fn display(&'a self, options: DisplayOptions<'a>) -> CompareResultDisplay<'a> {
         CompareResultDisplay {
             result: self,
             options,
         }
     }
+
+    /// Estimates the size in bytes of the [`display`](struct.CompareResult#method.display) output
+    /// without rendering it, so callers can decide up front whether to render it fully, truncate
+    /// it or write it to a file. It is exact for the [`Plain`](struct.Plain.html) painter, and
+    /// styling of other painters comes on top.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::{Comparison, DisplayOptions, Plain};
+    /// let result = Comparison::new(&["foo", "bar"], &["foo", "baz"]).compare_infallible();
+    /// let options = DisplayOptions::default().painter(&Plain);
+    /// assert_eq!(result.rendered_size_hint(options), result.display(options).to_string().len());
+    /// ```
+    pub fn rendered_size_hint(&self, options: DisplayOptions<'_>) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let number = |pos: Option<usize>| {
+            pos.map_or(3, |pos| (pos + options.offset).to_string().len().max(3))
+        };
+        let hunks: usize = self
+            .hunks
+            .iter()
+            .map(|hunk| {
+                let header = "... ...   ".len() + hunk.header(options.offset).len() + 1;
+                let lines: usize = rows(hunk, options)
+                    .into_iter()
+                    .map(|row| {
+                        let line = match row {
+                            Row::Line(line) => line,
+                            Row::Collapsed(count) => return collapsed_marker(count).len() + 1,
+                        };
+                        // Gutter is `iii jjj   `, `    jjj  +` or `iii      -`.
+                        let gutter = match line.kind {
+                            LineKind::Unchanged => number(line.old_pos) + number(line.new_pos) + 4,
+                            LineKind::Inserted | LineKind::ReplaceInserted => {
+                                number(line.new_pos) + 7
+                            }
+                            _ => number(line.old_pos) + 7,
+                        };
+                        let notes: usize = notes(line).iter().map(String::len).sum();
+                        let isolates = match options.isolate_rtl && has_rtl(line.inner) {
+                            true => "\u{2068}\u{2069}".len(),
+                            false => 0,
+                        };
+                        gutter + line.inner.len() + isolates + notes + 1
+                    })
+                    .sum();
+                header + lines
+            })
+            .sum();
+        let label = options
+            .severity
+            .map_or(0, |severity| severity.label().len());
+        "\n".len() + label + options.msg_fmt.len() + "\n\n".len() + hunks + self.hunks.len() - 1
+    }
+
+    /// Splits the output of [`display`](struct.CompareResult#method.display) into pages of
+    /// `page_size` hunks each, so interactive tools can show them one by one. Only the first
+    /// page contains `msg_fmt`. Pages joined with a new line give exactly the same output as
+    /// `display`. Page size of 0 is treated as 1.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::{Comparison, DisplayOptions};
+    /// let left = ["a", "b", "c", "d", "e", "f", "g", "h"];
+    /// let right = ["A", "b", "c", "d", "e", "f", "g", "H"];
+    /// let result = Comparison { context_radius: 0, ..Comparison::new(&left, &right) }
+    ///     .compare()
+    ///     .unwrap();
+    /// assert_eq!(result.display_paged(DisplayOptions::default(), 1).count(), 2);
+    /// ```
+    pub fn display_paged(
+        &'a self,
+        options: DisplayOptions<'a>,
+        page_size: usize,
+    ) -> CompareResultPages<'a> {
+        CompareResultPages {
+            chunks: self.hunks.chunks(page_size.max(1)),
+            first: true,
+            options,
+        }
+    }
 }
 
-/// Structure which implements [`Display`](std::fmt::Display) with ANSI escape color codes. It is a
+/// Structure which implements [`Display`](std::fmt::Display), styled by a [`Painter`](trait.Painter.html). It is a
 /// wrapper to the [`CompareResult`](struct.CompareResult.html).
 #[derive(Debug)]
 pub struct CompareResultDisplay<'a> {
@@ -23,20 +111,193 @@ pub struct CompareResultDisplay<'a> {
 impl<'a> fmt::Display for CompareResultDisplay<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if !self.result.is_empty() {
-            let mut msg = String::from("\n");
-            msg += self.options.msg_fmt;
-            msg += "\n\n";
-
-            msg += &self
-                .result
-                .hunks
-                .iter()
-                .map(|s| s.display(self.options).to_string())
-                .join("\n");
-
-            write!(f, "{}", msg)
+            write_hunks(f, &self.result.hunks, self.options, true)
         } else {
             Ok(())
         }
     }
 }
+
+/// Iterator over pages of the [`CompareResult`](struct.CompareResult.html) output, returned by
+/// [`display_paged`](struct.CompareResult#method.display_paged) method.
+#[derive(Debug)]
+pub struct CompareResultPages<'a> {
+    chunks: Chunks<'a, Hunk<'a>>,
+    first: bool,
+    options: DisplayOptions<'a>,
+}
+
+impl<'a> Iterator for CompareResultPages<'a> {
+    type Item = CompareResultPage<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let hunks = self.chunks.next()?;
+        let with_message = std::mem::replace(&mut self.first, false);
+        Some(CompareResultPage {
+            hunks,
+            with_message,
+            options: self.options,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+/// Structure which implements [`Display`](std::fmt::Display), styled by a [`Painter`](trait.Painter.html). It
+/// contains one page of hunks.
+#[derive(Debug)]
+pub struct CompareResultPage<'a> {
+    hunks: &'a [Hunk<'a>],
+    with_message: bool,
+    options: DisplayOptions<'a>,
+}
+
+impl<'a> fmt::Display for CompareResultPage<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_hunks(f, self.hunks, self.options, self.with_message)
+    }
+}
+
+pub(super) fn write_hunks(
+    f: &mut fmt::Formatter,
+    hunks: &[Hunk],
+    options: DisplayOptions,
+    with_message: bool,
+) -> fmt::Result {
+    let mut msg = String::new();
+    if with_message {
+        msg += "\n";
+        msg += &options.message();
+        msg += "\n\n";
+    }
+
+    msg += &hunks
+        .iter()
+        .map(|s| s.display(options).to_string())
+        .join("\n");
+
+    write!(f, "{}", msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Comparison, DisplayOptions};
+    use itertools::Itertools;
+
+    #[test]
+    fn pages_join_into_full_output() {
+        let left = (0..40)
+            .map(|i| if i % 7 == 0 { "x" } else { "a" })
+            .collect::<Vec<_>>();
+        let right = (0..40)
+            .map(|i| if i % 9 == 0 { "y" } else { "a" })
+            .collect::<Vec<_>>();
+        let result = Comparison {
+            context_radius: 1,
+            ..Comparison::new(&left, &right)
+        }
+        .compare()
+        .unwrap();
+        let options = DisplayOptions {
+            msg_fmt: "Found differences",
+            painter: &crate::Plain,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            result.rendered_size_hint(options),
+            result.display(options).to_string().len()
+        );
+        for page_size in 0..4 {
+            let pages = result.display_paged(options, page_size).collect::<Vec<_>>();
+            assert_eq!(pages.len(), result.hunks().len().div_ceil(page_size.max(1)));
+            assert_eq!(pages.iter().join("\n"), result.display(options).to_string());
+        }
+    }
+
+    #[test]
+    fn size_hint_with_wide_numbers() {
+        let left = (0..1200)
+            .map(|i| if i % 300 == 0 { "x" } else { "a" })
+            .collect::<Vec<_>>();
+        let right = (0..1300)
+            .map(|i| if i % 400 == 0 { "yy" } else { "a" })
+            .collect::<Vec<_>>();
+        let result = Comparison::new(&left, &right).compare_infallible();
+        for offset in [0, 1, 9_000].iter().copied() {
+            let options = DisplayOptions {
+                offset,
+                painter: &crate::Plain,
+                ..Default::default()
+            };
+            assert_eq!(
+                result.rendered_size_hint(options),
+                result.display(options).to_string().len()
+            );
+        }
+        assert_eq!(
+            Comparison::new(&left, &left)
+                .compare_infallible()
+                .rendered_size_hint(Default::default()),
+            0
+        );
+    }
+
+    #[test]
+    fn collapsed_unchanged_runs() {
+        let left = ["x", "a", "b", "c", "d", "e", "y", "f"];
+        let right = ["X", "a", "b", "c", "d", "e", "Y", "f"];
+        let result = Comparison {
+            context_radius: 3,
+            interhunk_lines: Some(5),
+            ..Comparison::new(&left, &right)
+        }
+        .compare_infallible();
+        let options = DisplayOptions {
+            painter: &crate::Plain,
+            collapse_unchanged: Some(1),
+            ..Default::default()
+        };
+        let output = result.display(options).to_string();
+        assert_eq!(
+            output,
+            "\n\n\n\
+             ... ...   @@ -1,8 +1,8 @@\n\
+             001      -x\n\
+             \x20   001  +X\n\
+             002 002   a\n\
+             ... ...   … 3 unchanged lines …\n\
+             006 006   e\n\
+             007      -y\n\
+             \x20   007  +Y\n\
+             008 008   f\n"
+        );
+        assert_eq!(result.rendered_size_hint(options), output.len());
+
+        let options = DisplayOptions {
+            collapse_unchanged: Some(3),
+            ..options
+        };
+        assert!(!result
+            .display(options)
+            .to_string()
+            .contains("unchanged lines"));
+    }
+
+    #[test]
+    fn size_hint_with_isolated_rtl() {
+        let result =
+            Comparison::new(&["שלום", "a", "b"], &["שלום!", "a", "c"]).compare_infallible();
+        let options = DisplayOptions {
+            painter: &crate::Plain,
+            isolate_rtl: true,
+            ..Default::default()
+        };
+        let output = result.display(options).to_string();
+        assert!(output.contains("001      -\u{2068}שלום\u{2069}\n"));
+        assert!(output.contains("002 002   a\n"));
+        assert_eq!(result.rendered_size_hint(options), output.len());
+    }
+}
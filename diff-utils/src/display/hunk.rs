@@ -1,20 +1,41 @@
 use crate::display::line_diff::LineDiff;
+use crate::display::{Plain, Style};
 use crate::{DisplayOptions, Hunk, Line, LineKind};
-use colored::Colorize;
 use std::collections::BTreeMap;
 use std::fmt;
 
 impl<'a> Hunk<'a> {
-    /// Returns a structure which implements [`Display`](std::fmt::Display) with ANSI escape color codes.
+    /// Returns a structure which implements [`Display`](std::fmt::Display), styled by the painter
+    /// from `options`.
     pub fn display(&'a self, options: DisplayOptions<'a>) -> HunkDisplay<'a> {
         HunkDisplay {
             hunk: self,
             options,
         }
     }
+
+    /// The same as [`display`](#method.display) with default options, but never colored and
+    /// independent of the terminal and global `colored` settings, so it is stable enough for
+    /// snapshot tests.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let result = Comparison::new(&["foo"], &["bar"]).compare_infallible();
+    /// assert_eq!(
+    ///     result.hunks()[0].display_plain().to_string(),
+    ///     "... ...   @@ -1 +1 @@\n001      -foo\n    001  +bar\n"
+    /// );
+    /// ```
+    pub fn display_plain(&'a self) -> HunkDisplay<'a> {
+        self.display(DisplayOptions {
+            painter: &Plain,
+            ..Default::default()
+        })
+    }
 }
 
-/// Structure which implements [`Display`](std::fmt::Display) with ANSI escape color codes. It is a
+/// Structure which implements [`Display`](std::fmt::Display), styled by a [`Painter`](trait.Painter.html). It is a
 /// wrapper to the [`Hunk`](struct.Hunk.html).
 #[derive(Debug)]
 pub struct HunkDisplay<'a> {
@@ -32,16 +53,22 @@ impl<'a> fmt::Display for HunkDisplay<'a> {
             .filter_map(|line| get_with_pos(line).map(|key| (key, (*line).clone())))
             .collect::<BTreeMap<(usize, LineKind), Line>>();
 
-        let header = format!(
-            "... ...   @@ -{},{} +{},{} @@",
-            self.hunk.old_start + self.options.offset,
-            self.hunk.removed,
-            self.hunk.new_start + self.options.offset,
-            self.hunk.inserted
-        );
-        writeln!(f, "{}", header.black().dimmed())?;
+        let header = format!("... ...   {}", self.hunk.header(self.options.offset));
+        writeln!(
+            f,
+            "{}",
+            self.options.painter.paint(Style::HunkHeader, &header)
+        )?;
 
-        for line in self.hunk.lines.iter() {
+        for row in rows(self.hunk, self.options) {
+            let line = match row {
+                Row::Line(line) => line,
+                Row::Collapsed(count) => {
+                    let marker = collapsed_marker(count);
+                    writeln!(f, "{}", self.options.painter.paint(Style::Note, &marker))?;
+                    continue;
+                }
+            };
             if let Some(inverted) = get_inverted(line).and_then(|key| lines.get(&key)) {
                 LineDiff {
                     left: inverted,
@@ -58,6 +85,38 @@ impl<'a> fmt::Display for HunkDisplay<'a> {
     }
 }
 
+/// What is written for a line of the hunk, after collapsing unchanged runs.
+pub(crate) enum Row<'a> {
+    Line(&'a Line<'a>),
+    Collapsed(usize),
+}
+
+/// Lines of the `hunk` to display, with long unchanged runs collapsed according to `options`.
+pub(crate) fn rows<'a>(hunk: &'a Hunk<'a>, options: DisplayOptions) -> Vec<Row<'a>> {
+    let keep = match options.collapse_unchanged {
+        Some(keep) => keep,
+        None => return hunk.lines.iter().map(Row::Line).collect(),
+    };
+    let mut rows = Vec::with_capacity(hunk.lines.len());
+    let runs = hunk
+        .lines
+        .chunk_by(|a, b| (a.kind == LineKind::Unchanged) == (b.kind == LineKind::Unchanged));
+    for run in runs {
+        if run[0].kind != LineKind::Unchanged || run.len() <= keep * 2 {
+            rows.extend(run.iter().map(Row::Line));
+            continue;
+        }
+        rows.extend(run[..keep].iter().map(Row::Line));
+        rows.push(Row::Collapsed(run.len() - keep * 2));
+        rows.extend(run[run.len() - keep..].iter().map(Row::Line));
+    }
+    rows
+}
+
+pub(crate) fn collapsed_marker(count: usize) -> String {
+    format!("... ...   … {} unchanged lines …", count)
+}
+
 fn get_with_pos(line: &Line) -> Option<(usize, LineKind)> {
     match line.kind {
         LineKind::ReplaceRemoved => Some((line.old_pos?, line.kind)),
@@ -1,19 +1,37 @@
-use crate::display::DisplayOptions;
+use crate::display::unicode::isolate_rtl;
+use crate::display::{DisplayOptions, Plain, Style};
 use crate::{Line, LineKind};
-use colored::Colorize;
 use std::fmt;
 
 impl<'a> Line<'a> {
-    /// Returns a structure which implements [`Display`](std::fmt::Display) with ANSI escape color codes.
+    /// Returns a structure which implements [`Display`](std::fmt::Display), styled by the painter
+    /// from `options`.
     pub fn display(&'a self, options: DisplayOptions<'a>) -> LineDisplay<'a> {
         LineDisplay {
             line: self,
             options,
         }
     }
+
+    /// The same as [`display`](#method.display) with default options, but never colored, e.g.
+    /// for logging or custom reporters.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let result = Comparison::new(&["foo"], &["foo", "bar"]).compare_infallible();
+    /// let inserted = &result.hunks()[0].lines()[1];
+    /// assert_eq!(inserted.display_plain().to_string(), "    002  +bar");
+    /// ```
+    pub fn display_plain(&'a self) -> LineDisplay<'a> {
+        self.display(DisplayOptions {
+            painter: &Plain,
+            ..Default::default()
+        })
+    }
 }
 
-/// Structure which implements [`Display`](std::fmt::Display) with ANSI escape color codes. It is a
+/// Structure which implements [`Display`](std::fmt::Display), styled by a [`Painter`](trait.Painter.html). It is a
 /// wrapper to the [`Line`](struct.Line.html).
 #[derive(Debug)]
 pub struct LineDisplay<'a> {
@@ -23,37 +41,150 @@ pub struct LineDisplay<'a> {
 
 impl<'a> fmt::Display for LineDisplay<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let line = self.line.inner;
+        let content = match self.options.isolate_rtl {
+            true => isolate_rtl(self.line.inner),
+            false => self.line.inner.into(),
+        };
+        let line = &*content;
         let i = self.line.old_pos.map(|p| p + self.options.offset);
         let j = self.line.new_pos.map(|p| p + self.options.offset);
         let sign = self.line.kind.sign();
+        let painter = self.options.painter;
 
         let header = match self.line.kind {
             LineKind::Inserted | LineKind::ReplaceInserted => {
-                format!("    {:03}  {}", j.unwrap(), sign.bold())
+                format!(
+                    "    {:03}  {}",
+                    j.unwrap(),
+                    painter.paint(Style::Sign, sign)
+                )
             }
             LineKind::Removed | LineKind::ReplaceRemoved => {
-                format!("{:03}      {}", i.unwrap(), sign.bold())
+                format!(
+                    "{:03}      {}",
+                    i.unwrap(),
+                    painter.paint(Style::Sign, sign)
+                )
             }
             LineKind::Unchanged => format!("{:03} {:03}   ", i.unwrap(), j.unwrap()),
         };
 
         match self.line.kind {
             LineKind::Inserted | LineKind::ReplaceInserted => {
-                write!(f, "{}", header.green())
+                write!(f, "{}", painter.paint(Style::InsertedGutter, &header))
             }
             LineKind::Removed | LineKind::ReplaceRemoved => {
-                write!(f, "{}", header.red())
+                write!(f, "{}", painter.paint(Style::RemovedGutter, &header))
             }
             LineKind::Unchanged => write!(f, "{}", header),
         }?;
 
         match self.line.kind {
-            LineKind::ReplaceInserted => write!(f, "{}", line.green()),
-            LineKind::ReplaceRemoved => write!(f, "{}", line.red()),
-            LineKind::Inserted => write!(f, "{}", line.on_green().black()),
-            LineKind::Removed => write!(f, "{}", line.on_red().black()),
+            LineKind::ReplaceInserted => {
+                write!(f, "{}", painter.paint(Style::ReplaceInserted, line))
+            }
+            LineKind::ReplaceRemoved => write!(f, "{}", painter.paint(Style::ReplaceRemoved, line)),
+            LineKind::Inserted => write!(f, "{}", painter.paint(Style::Inserted, line)),
+            LineKind::Removed => write!(f, "{}", painter.paint(Style::Removed, line)),
             LineKind::Unchanged => write!(f, "{}", line),
+        }?;
+
+        for note in notes(self.line) {
+            write!(f, "{}", painter.paint(Style::Note, &note))?;
         }
+        Ok(())
+    }
+}
+
+/// Remarks written after a changed line.
+pub(crate) fn notes(line: &Line) -> Vec<String> {
+    let mut notes = Vec::new();
+    if line.kind == LineKind::Unchanged {
+        return notes;
+    }
+    let occurrence = line.occurrence;
+    if occurrence.is_repeated() {
+        notes.push(format!(
+            "  ({} of {} occurrences)",
+            ordinal(occurrence.index),
+            occurrence.count
+        ));
+    }
+    // Lossy conversion of bytes, e.g. by `String::from_utf8_lossy`, shouldn't pass unnoticed.
+    if line.inner.contains(char::REPLACEMENT_CHARACTER) {
+        notes.push("  (invalid UTF-8 replaced with \u{FFFD})".to_string());
+    }
+    notes
+}
+
+fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Occurrence;
+
+    const PLAIN: DisplayOptions = DisplayOptions {
+        offset: 1,
+        msg_fmt: "",
+        painter: &Plain,
+        isolate_rtl: false,
+        collapse_unchanged: None,
+        severity: None,
+    };
+
+    #[test]
+    fn ordinals() {
+        let ordinals = [1, 2, 3, 4, 11, 12, 13, 21, 22, 101, 111]
+            .iter()
+            .map(|n| ordinal(*n))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            ordinals,
+            [
+                "1st", "2nd", "3rd", "4th", "11th", "12th", "13th", "21st", "22nd", "101st",
+                "111th"
+            ]
+        );
+    }
+
+    #[test]
+    fn repeated_line() {
+        let line = Line::remove(4, "foo").with_occurrence(Occurrence { index: 3, count: 4 });
+        assert_eq!(
+            "005      -foo  (3rd of 4 occurrences)",
+            line.display(PLAIN).to_string()
+        );
+
+        let line = Line::unchanged(4, 4, "foo").with_occurrence(Occurrence { index: 3, count: 4 });
+        assert_eq!("005 005   foo", line.display(PLAIN).to_string());
+    }
+
+    #[test]
+    fn plain_line() {
+        let line = Line::replace_remove(0, Some(0), "foo");
+        assert_eq!(
+            line.display_plain().to_string(),
+            line.display(PLAIN).to_string()
+        );
+    }
+
+    #[test]
+    fn invalid_utf8_line() {
+        let text = String::from_utf8_lossy(b"fo\xffo").into_owned();
+        let line = Line::insert(0, &text);
+        assert_eq!(
+            "    001  +fo\u{FFFD}o  (invalid UTF-8 replaced with \u{FFFD})",
+            line.display(PLAIN).to_string()
+        );
     }
 }
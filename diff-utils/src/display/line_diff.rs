@@ -1,5 +1,8 @@
-use crate::{Comparison, DisplayOptions, Line, LineKind};
-use colored::Colorize;
+use crate::display::unicode::clusters;
+use crate::display::{Painter, Style};
+use crate::ops::{self, Recorder};
+use crate::processor::Processor;
+use crate::{cleanup, DisplayOptions, Line, LineKind};
 use itertools::Itertools;
 use std::fmt;
 
@@ -9,14 +12,200 @@ pub(crate) struct LineDiff<'a> {
     pub(crate) options: DisplayOptions<'a>,
 }
 
+/// Part of the right line after refining it against the left one, cluster by cluster, so
+/// combining marks and emoji sequences are never highlighted apart from their base character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Segment<'a> {
+    /// Clusters present in both lines
+    Unchanged(&'a str),
+    /// Clusters present only in the right line
+    Changed(&'a str),
+}
+
+impl<'a> Segment<'a> {
+    /// Segment of the `right` clusters represented by a single-cluster line of the
+    /// refinement, or `None` for clusters which exist only in the left line.
+    fn of(letter: &Line, right: &[&'a str]) -> Option<Self> {
+        let text = || letter.new_pos.and_then(|pos| right.get(pos).copied());
+        match letter.kind {
+            LineKind::Unchanged => text().map(Segment::Unchanged),
+            LineKind::Inserted | LineKind::ReplaceInserted => text().map(Segment::Changed),
+            LineKind::Removed | LineKind::ReplaceRemoved => None,
+        }
+    }
+
+    fn paint(self, painter: &dyn Painter) -> String {
+        match self {
+            Segment::Unchanged(text) => painter.paint(Style::InlineUnchanged, text),
+            Segment::Changed(text) => painter.paint(Style::InlineChanged, text),
+        }
+    }
+}
+
+/// Splits `right` into segments which are unchanged or changed compared to `left`. Returns
+/// `None` if both lines are equal. Segments always cover the whole `right`, in order, however
+/// the refinement is split into hunks, and if the refinement fails, the whole `right` is
+/// a single changed segment.
+///
+/// Clusters can often be aligned in many equally good ways, e.g. `ab` inserted into `abab`.
+/// Changes are then always placed at the earliest possible position, see `cleanup::earliest`.
+pub(crate) fn segments<'a>(left: &'a str, right: &'a str) -> Option<Vec<Segment<'a>>> {
+    let (l, r) = (clusters(left), clusters(right));
+    if l == r {
+        return None;
+    }
+
+    let mut recorder = Recorder::default();
+    let mut processor = Processor::new(&l, &r, 0, 0);
+    let refined = diffs::patience::diff(&mut recorder, &l, 0, l.len(), &r, 0, r.len())
+        .map(|()| cleanup::earliest(recorder.result(), &l, &r))
+        .and_then(|ops| ops::replay(&ops, &mut diffs::Replace::new(&mut processor)));
+    if refined.is_err() {
+        // Rendering never fails, the whole line is highlighted instead.
+        return Some(vec![Segment::Changed(right)]);
+    }
+
+    // Clusters between and after hunks are unchanged.
+    let mut segments = Vec::with_capacity(r.len());
+    let mut next = 0;
+    for hunk in &processor.result() {
+        let start = hunk.new_start.clamp(next, r.len());
+        segments.extend(r[next..start].iter().copied().map(Segment::Unchanged));
+        next = start;
+        for letter in &hunk.lines {
+            if let Some(segment) = Segment::of(letter, &r) {
+                segments.push(segment);
+                next = letter.new_pos.map_or(next, |pos| pos + 1);
+            }
+        }
+    }
+    segments.extend(
+        r[next.min(r.len())..]
+            .iter()
+            .copied()
+            .map(Segment::Unchanged),
+    );
+    Some(segments)
+}
+
+impl<'a> fmt::Display for LineDiff<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let segments = match segments(self.left.inner, self.right.inner) {
+            Some(segments) => segments,
+            None => return writeln!(f, "{}", self.right.display(self.options)),
+        };
+        let painter = self.options.painter;
+        let line = segments
+            .into_iter()
+            .map(|segment| segment.paint(painter))
+            .join("");
+
+        let line = Line {
+            inner: &line,
+            ..self.right.clone()
+        };
+
+        let fmt = line.display(self.options);
+        writeln!(f, "{}", fmt)?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn unicode_support() {
-        colored::control::set_override(false);
+    fn segments_of_right_line() {
+        assert_eq!(segments("same", "same"), None);
+        assert_eq!(
+            segments("Pośród", "Posród"),
+            Some(
+                ["P", "o"]
+                    .iter()
+                    .map(|c| Segment::Unchanged(c))
+                    .chain(Some(Segment::Changed("s")))
+                    .chain(["r", "ó", "d"].iter().map(|c| Segment::Unchanged(c)))
+                    .collect()
+            )
+        );
+        assert_eq!(segments("abc", ""), Some(vec![]));
+    }
 
+    #[test]
+    fn combining_marks_stay_with_base() {
+        assert_eq!(
+            segments("cafe", "cafe\u{301}"),
+            Some(vec![
+                Segment::Unchanged("c"),
+                Segment::Unchanged("a"),
+                Segment::Unchanged("f"),
+                Segment::Changed("e\u{301}"),
+            ])
+        );
+    }
+
+    #[test]
+    fn multiple_hunks() {
+        let left = "a-bcdefghijklmnopqrstuvwxyz-z";
+        let right = "a+bcdefghijklmnopqrstuvwxyz+z";
+        let changed = segments(left, right)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+            .filter(|(_, segment)| matches!(segment, Segment::Changed(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changed,
+            vec![(1, Segment::Changed("+")), (27, Segment::Changed("+"))]
+        );
+    }
+
+    /// Random pairs of unicode strings, including combining marks and astral characters.
+    #[test]
+    fn segments_cover_right_line() {
+        const CHARS: &[char] = &['a', 'b', ' ', 'ą', '\u{301}', '🙂', '字', '\t'];
+        let mut seed: u64 = 7;
+        let mut random = |len: u64| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (seed >> 33) % len
+        };
+        for _ in 0..500 {
+            let mut text = || {
+                let len = random(12);
+                (0..len)
+                    .map(|_| CHARS[random(CHARS.len() as u64) as usize])
+                    .collect::<String>()
+            };
+            let (left, right) = (text(), text());
+
+            match segments(&left, &right) {
+                None => assert_eq!(left, right),
+                Some(segments) => {
+                    let joined = segments
+                        .iter()
+                        .map(|segment| match segment {
+                            Segment::Unchanged(text) | Segment::Changed(text) => *text,
+                        })
+                        .collect::<String>();
+                    assert_eq!(joined, right, "left: {:?}", left);
+                    let unchanged = segments.iter().filter_map(|segment| match segment {
+                        Segment::Unchanged(text) => Some(*text),
+                        Segment::Changed(_) => None,
+                    });
+                    let mut left_clusters = clusters(&left).into_iter();
+                    for c in unchanged {
+                        assert!(left_clusters.any(|l| l == c), "{:?} not in {:?}", c, left);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unicode_support() {
         let left = "Pośród";
         let right = "Posród"; // "s" instead of "ś".
 
@@ -26,65 +215,30 @@ mod tests {
         let diff = LineDiff {
             left: &left,
             right: &right,
-            options: Default::default(),
+            options: DisplayOptions {
+                painter: &crate::display::Plain,
+                ..Default::default()
+            },
         };
 
         assert_eq!("    003  +Posród\n", diff.to_string());
     }
-}
-
-impl<'a> fmt::Display for LineDiff<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let l = self
-            .left
-            .inner
-            .char_indices()
-            .map(|(idx, c)| &self.left.inner[idx..idx + c.len_utf8()])
-            .collect::<Vec<_>>();
-        let r = self
-            .right
-            .inner
-            .char_indices()
-            .map(|(idx, c)| &self.right.inner[idx..idx + c.len_utf8()])
-            .collect::<Vec<_>>();
-
-        let len = std::cmp::max(self.left.inner.len(), self.right.inner.len());
-        let diff = Comparison {
-            left: &l,
-            right: &r,
-            context_radius: len,
-        }
-        .compare()
-        .unwrap();
-        if diff.is_empty() {
-            return writeln!(f, "{}", self.right.display(self.options));
-        }
-        let hunk = &diff.hunks[0];
-
-        let line = hunk
-            .lines
-            .iter()
-            .filter(|l| l.kind != LineKind::Removed && l.kind != LineKind::ReplaceRemoved)
-            .map(|letter| {
-                if letter.kind == LineKind::Unchanged {
-                    format!("{}", letter.inner.dimmed())
-                } else if letter.kind == LineKind::Inserted
-                    || letter.kind == LineKind::ReplaceInserted
-                {
-                    format!("{}", letter.inner.reversed())
-                } else {
-                    unreachable!("Filters removed. Can't happen")
-                }
-            })
-            .join("");
 
-        let line = Line {
-            inner: &line,
-            ..self.right.clone()
-        };
+    #[test]
+    fn ties_take_earliest_position() {
+        use Segment::{Changed as C, Unchanged as U};
 
-        let fmt = line.display(self.options);
-        writeln!(f, "{}", fmt)?;
-        Ok(())
+        assert_eq!(
+            segments("ab", "abab"),
+            Some(vec![C("a"), C("b"), U("a"), U("b")])
+        );
+        assert_eq!(
+            segments("xaby", "xababy"),
+            Some(vec![U("x"), C("a"), C("b"), U("a"), U("b"), U("y")])
+        );
+        assert_eq!(
+            segments("a  b", "a   b"),
+            Some(vec![U("a"), C(" "), U(" "), U(" "), U("b")])
+        );
     }
 }
@@ -7,6 +7,11 @@ mod compare_result;
 mod hunk;
 mod line;
 mod line_diff;
+mod multi;
 mod options;
+mod style;
+mod unicode;
 
 pub use options::*;
+pub use style::*;
+pub use unicode::display_width;
@@ -0,0 +1,104 @@
+use crate::display::compare_result::write_hunks;
+use crate::display::Style;
+use crate::{DisplayOptions, MultiCompareResult};
+use std::fmt;
+
+impl<'a> MultiCompareResult<'a> {
+    /// Returns a structure which implements [`Display`](std::fmt::Display), styled by the painter
+    /// from `options`. Sections without differences are skipped, the rest is written under
+    /// `=== label ===` headers, after the `msg_fmt` written once for all of them.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::{DisplayOptions, MultiCompareResult, Plain};
+    /// let result = MultiCompareResult::compare(&[
+    ///     ("header", &["foo"], &["foo"]),
+    ///     ("body", &["bar"], &["baz"]),
+    /// ]);
+    /// let options = DisplayOptions::default().msg_fmt("Report changed").painter(&Plain);
+    /// assert_eq!(
+    ///     result.display(options).to_string(),
+    ///     "\nReport changed\n\n=== body ===\n... ...   @@ -1 +1 @@\n001      -bar\n    001  +baz\n"
+    /// );
+    /// ```
+    pub fn display(&'a self, options: DisplayOptions<'a>) -> MultiCompareResultDisplay<'a> {
+        MultiCompareResultDisplay {
+            result: self,
+            options,
+        }
+    }
+}
+
+/// Structure which implements [`Display`](std::fmt::Display), styled by a [`Painter`](trait.Painter.html). It is a
+/// wrapper to the [`MultiCompareResult`](struct.MultiCompareResult.html).
+#[derive(Debug)]
+pub struct MultiCompareResultDisplay<'a> {
+    result: &'a MultiCompareResult<'a>,
+    options: DisplayOptions<'a>,
+}
+
+impl<'a> fmt::Display for MultiCompareResultDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.result.is_empty() {
+            return Ok(());
+        }
+        write!(f, "\n{}\n\n", self.options.message())?;
+        for (idx, (label, result)) in self.result.changed().enumerate() {
+            if idx > 0 {
+                writeln!(f)?;
+            }
+            let header = format!("=== {} ===", label);
+            writeln!(
+                f,
+                "{}",
+                self.options.painter.paint(Style::HunkHeader, &header)
+            )?;
+            write_hunks(f, result.hunks(), self.options, false)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{DisplayOptions, MultiCompareResult, Plain};
+
+    #[test]
+    fn sections_under_labels() {
+        let mut result = MultiCompareResult::compare(&[
+            ("first", &["a", "b"], &["a", "c"]),
+            ("same", &["x"], &["x"]),
+        ]);
+        assert!(!result.is_empty());
+        result.push(
+            "last",
+            crate::Comparison::new(&["y"], &[]).compare_infallible(),
+        );
+        let options = DisplayOptions {
+            painter: &Plain,
+            ..Default::default()
+        };
+        assert_eq!(
+            result.display(options).to_string(),
+            "\n\n\n\
+             === first ===\n\
+             ... ...   @@ -1,2 +1,2 @@\n\
+             001 001   a\n\
+             002      -b\n\
+             \x20   002  +c\n\
+             \n\
+             === last ===\n\
+             ... ...   @@ -1 +0,0 @@\n\
+             001      -y\n"
+        );
+    }
+
+    #[test]
+    fn equal_sections() {
+        let result = MultiCompareResult::compare(&[("a", &["x"], &["x"]), ("b", &[], &[])]);
+        assert!(result.is_empty());
+        assert_eq!(result.sections().len(), 2);
+        assert_eq!(result.display(DisplayOptions::default()).to_string(), "");
+        assert!(MultiCompareResult::default().is_empty());
+    }
+}
@@ -1,5 +1,15 @@
-/// Options for displaying diffs.
+use crate::display::style::{Painter, Severity, DEFAULT_PAINTER};
+use crate::RenderOptions;
+
+/// Options for displaying diffs. New options may be added in any release, so outside of this
+/// crate it is built from the default with builder methods named after the fields:
+/// ```rust
+/// use diff_utils::{DisplayOptions, Plain};
+/// let options = DisplayOptions::default().offset(0).painter(&Plain);
+/// assert_eq!(options.offset, 0);
+/// ```
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub struct DisplayOptions<'a> {
     /// Sometimes user want's to compare only subslice of a full str. This argument gives
     /// possibility to "move" whole diff to proper offset.
@@ -15,7 +25,7 @@ pub struct DisplayOptions<'a> {
     /// let subslice_b = file_b.into_iter().skip(123).take(10).collect::<Vec<&str>>();
     ///
     /// let result = Comparison::new(&subslice_a, &subslice_b).compare().unwrap();
-    /// println!("{}", result.display(DisplayOptions { offset: 123, ..Default::default() }));
+    /// println!("{}", result.display(DisplayOptions::default().offset(123)));
     /// ```
     ///
     /// Thanks to the `offset` the output will be:
@@ -41,6 +51,83 @@ pub struct DisplayOptions<'a> {
     /// Print extra message before writing diff itself.
     /// It is mostly used to specify the filenames
     pub msg_fmt: &'a str,
+    /// Styling backend.
+    ///
+    /// Default value: [`Colored`](struct.Colored.html) with `colored` feature, otherwise
+    /// [`Plain`](struct.Plain.html)
+    pub painter: &'a dyn Painter,
+    /// Wraps contents of lines with right-to-left text, like Hebrew or Arabic, in Unicode
+    /// isolates (`U+2068`..`U+2069`), so terminals don't visually reorder them together with
+    /// line numbers and signs.
+    ///
+    /// Default value: false
+    pub isolate_rtl: bool,
+    /// Shortens runs of more than twice this many unchanged lines inside of hunks, e.g. coming
+    /// from big context radius, to this many lines at both ends and a
+    /// `… 42 unchanged lines …` marker between them. Patches always contain all the lines.
+    ///
+    /// Default value: `None` - all lines are shown
+    pub collapse_unchanged: Option<usize>,
+    /// Writes a label like `warning: ` before the `msg_fmt`.
+    ///
+    /// Default value: `None` - no label
+    pub severity: Option<Severity>,
+}
+
+impl<'a> DisplayOptions<'a> {
+    /// Sets [`offset`](#structfield.offset)
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Takes options shared with patches, i.e. the [`offset`](#structfield.offset)
+    pub fn render(mut self, render: &RenderOptions) -> Self {
+        self.offset = render.offset;
+        self
+    }
+
+    /// Sets [`msg_fmt`](#structfield.msg_fmt)
+    pub fn msg_fmt(mut self, msg_fmt: &'a str) -> Self {
+        self.msg_fmt = msg_fmt;
+        self
+    }
+
+    /// Sets [`painter`](#structfield.painter)
+    pub fn painter(mut self, painter: &'a dyn Painter) -> Self {
+        self.painter = painter;
+        self
+    }
+
+    /// Sets [`isolate_rtl`](#structfield.isolate_rtl)
+    pub fn isolate_rtl(mut self, isolate_rtl: bool) -> Self {
+        self.isolate_rtl = isolate_rtl;
+        self
+    }
+
+    /// Sets [`collapse_unchanged`](#structfield.collapse_unchanged)
+    pub fn collapse_unchanged(mut self, keep: usize) -> Self {
+        self.collapse_unchanged = Some(keep);
+        self
+    }
+
+    /// Sets [`severity`](#structfield.severity)
+    pub fn severity(mut self, severity: Severity) -> Self {
+        self.severity = Some(severity);
+        self
+    }
+
+    /// Message preceding the diff, with the severity label.
+    pub(crate) fn message(&self) -> String {
+        match self.severity {
+            Some(severity) => format!(
+                "{}{}",
+                self.painter.paint(severity.style(), severity.label()),
+                self.msg_fmt
+            ),
+            None => self.msg_fmt.to_string(),
+        }
+    }
 }
 
 impl<'a> Default for DisplayOptions<'a> {
@@ -48,6 +135,10 @@ impl<'a> Default for DisplayOptions<'a> {
         Self {
             offset: 1,
             msg_fmt: Default::default(),
+            painter: &DEFAULT_PAINTER,
+            isolate_rtl: false,
+            collapse_unchanged: None,
+            severity: None,
         }
     }
 }
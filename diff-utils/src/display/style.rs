@@ -0,0 +1,202 @@
+use std::fmt;
+
+/// Role of a piece of text in the diff output. [`Painter`](trait.Painter.html) decides how it
+/// looks like.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum Style {
+    /// Hunk header, e.g. `... ...   @@ -1,3 +1,4 @@`
+    HunkHeader,
+    /// Line numbers and the sign of an inserted line
+    InsertedGutter,
+    /// Line numbers and the sign of a removed line
+    RemovedGutter,
+    /// The `+`/`-` sign itself, inside of the gutter
+    Sign,
+    /// Content of an inserted line
+    Inserted,
+    /// Content of a removed line
+    Removed,
+    /// Content of a line inserted in place of another one
+    ReplaceInserted,
+    /// Content of a line replaced by another one
+    ReplaceRemoved,
+    /// Part of a replaced line which didn't change
+    InlineUnchanged,
+    /// Part of a replaced line which did change
+    InlineChanged,
+    /// Additional remarks, like which occurrence of a repeated line it is
+    Note,
+    /// Label of a comparison with [`Severity::Error`](enum.Severity.html#variant.Error)
+    Error,
+    /// Label of a comparison with [`Severity::Warning`](enum.Severity.html#variant.Warning)
+    Warning,
+}
+
+/// How serious differences of a whole comparison are, written as a label before the message,
+/// e.g. to tell tolerated drift from real failures apart.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[non_exhaustive]
+pub enum Severity {
+    /// `error: `, styled with [`Style::Error`](enum.Style.html#variant.Error)
+    Error,
+    /// `warning: `, styled with [`Style::Warning`](enum.Style.html#variant.Warning)
+    Warning,
+}
+
+impl Severity {
+    /// Label written before the message
+    pub fn label(self) -> &'static str {
+        match self {
+            Severity::Error => "error: ",
+            Severity::Warning => "warning: ",
+        }
+    }
+
+    /// Style of the label
+    pub fn style(self) -> Style {
+        match self {
+            Severity::Error => Style::Error,
+            Severity::Warning => Style::Warning,
+        }
+    }
+}
+
+/// Styling backend of the `display` feature. Implement it to plug a different terminal styling
+/// library, or to render diffs as e.g. HTML.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::{Comparison, DisplayOptions, Painter, Style};
+///
+/// #[derive(Debug)]
+/// struct Brackets;
+///
+/// impl Painter for Brackets {
+///     fn paint(&self, style: Style, text: &str) -> String {
+///         match style {
+///             Style::Inserted | Style::ReplaceInserted => format!("[{}]", text),
+///             _ => text.to_string(),
+///         }
+///     }
+/// }
+///
+/// let result = Comparison::new(&["foo"], &["foo", "bar"]).compare_infallible();
+/// let output = result.display(DisplayOptions::default().painter(&Brackets));
+/// assert!(output.to_string().contains("+[bar]"));
+/// ```
+pub trait Painter: fmt::Debug + Sync {
+    /// Returns `text` styled according to its role
+    fn paint(&self, style: Style, text: &str) -> String;
+}
+
+/// Painter which leaves the text as it is. Used by default when `colored` feature is disabled.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Plain;
+
+impl Painter for Plain {
+    fn paint(&self, _: Style, text: &str) -> String {
+        text.to_string()
+    }
+}
+
+/// Painter which always emits ANSI escape color codes, without any dependency and regardless of
+/// the terminal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Ansi;
+
+impl Painter for Ansi {
+    fn paint(&self, style: Style, text: &str) -> String {
+        let codes = match style {
+            Style::HunkHeader => "2;30",
+            Style::InsertedGutter | Style::ReplaceInserted => "32",
+            Style::RemovedGutter | Style::ReplaceRemoved => "31",
+            Style::Sign => "1",
+            Style::Inserted => "30;42",
+            Style::Removed => "30;41",
+            Style::InlineUnchanged | Style::Note => "2",
+            Style::InlineChanged => "7",
+            Style::Error => "1;31",
+            Style::Warning => "1;33",
+        };
+        format!("\u{1b}[{}m{}\u{1b}[0m", codes, text)
+    }
+}
+
+/// Painter based on the [`colored`](https://docs.rs/colored) crate, so it respects its global
+/// settings and environment variables like `CLICOLOR`. Used by default with `colored` feature.
+#[cfg(feature = "colored")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Colored;
+
+#[cfg(feature = "colored")]
+impl Painter for Colored {
+    fn paint(&self, style: Style, text: &str) -> String {
+        use colored::Colorize;
+
+        let text = match style {
+            Style::HunkHeader => text.black().dimmed(),
+            Style::InsertedGutter | Style::ReplaceInserted => text.green(),
+            Style::RemovedGutter | Style::ReplaceRemoved => text.red(),
+            Style::Sign => text.bold(),
+            Style::Inserted => text.on_green().black(),
+            Style::Removed => text.on_red().black(),
+            Style::InlineUnchanged | Style::Note => text.dimmed(),
+            Style::InlineChanged => text.reversed(),
+            Style::Error => text.red().bold(),
+            Style::Warning => text.yellow().bold(),
+        };
+        text.to_string()
+    }
+}
+
+#[cfg(feature = "colored")]
+pub(crate) static DEFAULT_PAINTER: Colored = Colored;
+
+#[cfg(not(feature = "colored"))]
+pub(crate) static DEFAULT_PAINTER: Plain = Plain;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Comparison, DisplayOptions};
+
+    #[test]
+    fn ansi_regardless_of_terminal() {
+        let result = Comparison::new(&["foo"], &["bar"]).compare_infallible();
+        let output = result
+            .display(DisplayOptions {
+                painter: &Ansi,
+                ..Default::default()
+            })
+            .to_string();
+
+        assert!(output.contains("\u{1b}[2;30m... ...   @@ -1 +1 @@\u{1b}[0m\n"));
+        assert!(output.contains("\u{1b}[32m    001  \u{1b}[1m+\u{1b}[0m\u{1b}[0m"));
+        assert!(output.contains("\u{1b}[7mb\u{1b}[0m\u{1b}[7ma\u{1b}[0m\u{1b}[7mr\u{1b}[0m"));
+    }
+
+    #[test]
+    fn severity_label() {
+        let result = Comparison::new(&["foo"], &["bar"]).compare_infallible();
+        let output = result
+            .display(DisplayOptions {
+                msg_fmt: "Drift",
+                painter: &Ansi,
+                severity: Some(Severity::Warning),
+                ..Default::default()
+            })
+            .to_string();
+        assert!(output.starts_with("\n\u{1b}[1;33mwarning: \u{1b}[0mDrift\n\n"));
+
+        let options = DisplayOptions {
+            msg_fmt: "Drift",
+            painter: &Plain,
+            severity: Some(Severity::Error),
+            ..Default::default()
+        };
+        let output = result.display(options).to_string();
+        assert!(output.starts_with("\nerror: Drift\n\n"));
+        assert_eq!(result.rendered_size_hint(options), output.len());
+    }
+}
@@ -0,0 +1,162 @@
+/*
+
+Here is code for text which doesn't map one to one to characters on the screen
+
+*/
+use std::borrow::Cow;
+
+/// Splits `text` into extended grapheme clusters, the user-perceived characters.
+#[cfg(feature = "unicode")]
+pub(crate) fn clusters(text: &str) -> Vec<&str> {
+    unicode_segmentation::UnicodeSegmentation::graphemes(text, true).collect()
+}
+
+/// Splits `text` into user-perceived characters: a base character followed by combining marks,
+/// variation selectors, emoji modifiers and zero width joiner sequences. It is an approximation
+/// of extended grapheme clusters which covers the scripts and emoji seen in test data, the
+/// `unicode` feature uses the full segmentation rules instead.
+#[cfg(not(feature = "unicode"))]
+pub(crate) fn clusters(text: &str) -> Vec<&str> {
+    let mut clusters = Vec::new();
+    let mut start = 0;
+    let mut joined = false;
+    for (idx, c) in text.char_indices() {
+        if idx > start && !joined && !extends(c) {
+            clusters.push(&text[start..idx]);
+            start = idx;
+        }
+        joined = c == '\u{200D}';
+    }
+    if start < text.len() {
+        clusters.push(&text[start..]);
+    }
+    clusters
+}
+
+/// Whether `c` belongs to the cluster of the preceding character.
+#[cfg(not(feature = "unicode"))]
+fn extends(c: char) -> bool {
+    match c {
+        '\u{200B}' | '\u{200E}' | '\u{200F}' => false,
+        '\u{1F3FB}'..='\u{1F3FF}' => true,
+        _ => is_zero_width(c),
+    }
+}
+
+/// Combining marks, joiners, variation selectors and other characters taking no column.
+fn is_zero_width(c: char) -> bool {
+    matches!(c,
+        '\u{0300}'..='\u{036F}'
+        | '\u{1AB0}'..='\u{1AFF}'
+        | '\u{1DC0}'..='\u{1DFF}'
+        | '\u{200B}'..='\u{200F}'
+        | '\u{20D0}'..='\u{20FF}'
+        | '\u{FE00}'..='\u{FE0F}'
+        | '\u{FE20}'..='\u{FE2F}'
+        | '\u{E0100}'..='\u{E01EF}'
+    )
+}
+
+/// Whether `c` takes two columns, like East Asian wide characters and most emoji.
+fn is_wide(c: char) -> bool {
+    matches!(c,
+        '\u{1100}'..='\u{115F}'
+        | '\u{2E80}'..='\u{303E}'
+        | '\u{3041}'..='\u{33FF}'
+        | '\u{3400}'..='\u{4DBF}'
+        | '\u{4E00}'..='\u{9FFF}'
+        | '\u{A000}'..='\u{A4CF}'
+        | '\u{AC00}'..='\u{D7A3}'
+        | '\u{F900}'..='\u{FAFF}'
+        | '\u{FE30}'..='\u{FE4F}'
+        | '\u{FF00}'..='\u{FF60}'
+        | '\u{FFE0}'..='\u{FFE6}'
+        | '\u{1F1E6}'..='\u{1F1FF}'
+        | '\u{1F300}'..='\u{1F64F}'
+        | '\u{1F900}'..='\u{1F9FF}'
+        | '\u{20000}'..='\u{3FFFD}'
+    )
+}
+
+/// Number of terminal columns taken by `text`. Every user-perceived character takes as many
+/// columns as its base character: two for East Asian wide characters and most emoji, none for
+/// combining marks and joiners on their own, one otherwise.
+pub fn display_width(text: &str) -> usize {
+    clusters(text)
+        .into_iter()
+        .filter_map(|cluster| cluster.chars().next())
+        .map(|c| match c {
+            c if is_zero_width(c) => 0,
+            c if is_wide(c) => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// Whether `text` contains characters of right-to-left scripts, like Hebrew or Arabic.
+pub(crate) fn has_rtl(text: &str) -> bool {
+    text.chars().any(|c| {
+        matches!(c,
+            '\u{0590}'..='\u{08FF}'
+            | '\u{FB1D}'..='\u{FDFF}'
+            | '\u{FE70}'..='\u{FEFF}'
+            | '\u{10800}'..='\u{10FFF}'
+            | '\u{1E800}'..='\u{1EFFF}'
+        )
+    })
+}
+
+/// Wraps right-to-left `text` in a first strong isolate, so the terminal doesn't reorder it
+/// together with the gutter and the notes around it.
+pub(crate) fn isolate_rtl(text: &str) -> Cow<'_, str> {
+    match has_rtl(text) {
+        true => Cow::Owned(format!("\u{2068}{}\u{2069}", text)),
+        false => Cow::Borrowed(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combining_sequences() {
+        assert_eq!(clusters(""), Vec::<&str>::new());
+        assert_eq!(clusters("e\u{301}a"), ["e\u{301}", "a"]);
+        assert_eq!(clusters("\u{301}a"), ["\u{301}", "a"]);
+        assert_eq!(clusters("👍🏽!"), ["👍🏽", "!"]);
+        assert_eq!(clusters("👩\u{200D}💻x"), ["👩\u{200D}💻", "x"]);
+        assert_eq!(clusters("❤\u{FE0F}字"), ["❤\u{FE0F}", "字"]);
+    }
+
+    #[test]
+    #[cfg(feature = "unicode")]
+    fn segmentation_rules() {
+        assert_eq!(clusters("🇵🇱🇩🇪"), ["🇵🇱", "🇩🇪"]);
+        assert_eq!(clusters("कि"), ["कि"]);
+        assert_eq!(
+            clusters("\u{1100}\u{1161}\u{11A8}a"),
+            ["\u{1100}\u{1161}\u{11A8}", "a"]
+        );
+    }
+
+    #[test]
+    fn widths() {
+        assert_eq!(display_width(""), 0);
+        assert_eq!(display_width("abc"), 3);
+        assert_eq!(display_width("東京 a"), 6);
+        assert_eq!(display_width("e\u{301}"), 1);
+        assert_eq!(display_width("\u{200B}"), 0);
+        assert_eq!(display_width("👍🏽!"), 3);
+        assert_eq!(display_width("👩\u{200D}💻"), 2);
+    }
+
+    #[test]
+    fn rtl() {
+        assert!(has_rtl("abc שלום"));
+        assert!(has_rtl("مرحبا"));
+        assert!(!has_rtl("Pośród 字"));
+        assert_eq!(isolate_rtl("foo"), "foo");
+        assert_eq!(isolate_rtl("שלום 1"), "\u{2068}שלום 1\u{2069}");
+    }
+}
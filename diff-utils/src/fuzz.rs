@@ -0,0 +1,143 @@
+//! Helpers for fuzzing the whole pipeline: comparison, hunks splitting and patch application.
+//! Used by `cargo fuzz` targets in `fuzz/` and by the `fuzz` integration test.
+
+use crate::{CompareOptions, CompareResult, Comparison, FilePatch, PatchOptions, PatchSet};
+
+/// Small set of lines, so random texts share a lot of them, including blank lines and
+/// repeated closing braces which are the trickiest for the hunks splitting.
+const LINES: &[&str] = &["", "a", "b", "c", "fn x() {", "  body", "}", "\tdone"];
+
+/// Turns arbitrary bytes into two texts. Bytes before the first `0xff` build the left one, the
+/// rest the right one. Every byte picks one line.
+pub fn texts(data: &[u8]) -> (Vec<&'static str>, Vec<&'static str>) {
+    let split = data
+        .iter()
+        .position(|b| *b == 0xff)
+        .unwrap_or(data.len() / 2);
+    let (left, right) = data.split_at(split);
+    let right = right.get(1..).unwrap_or_default();
+
+    let lines = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .map(|b| LINES[*b as usize % LINES.len()])
+            .collect::<Vec<_>>()
+    };
+    (lines(left), lines(right))
+}
+
+/// Generates pseudo-random bytes for [`round_trip`](fn.round_trip.html), so the same checks can
+/// be run without a fuzzer.
+pub fn random_bytes(seed: u64, len: usize) -> Vec<u8> {
+    // xorshift64*, good enough and without any dependency.
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state >> 12;
+            state ^= state << 25;
+            state ^= state >> 27;
+            (state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 56) as u8
+        })
+        .collect()
+}
+
+/// Compares two texts built from `data` with various options and panics if hunks cannot
+/// reconstruct the right text from the left one, or the left one from the right one. The patch
+/// of the hunks is also rendered, parsed back and applied on the left text.
+pub fn round_trip(data: &[u8]) {
+    let (left, right) = texts(data);
+    let variants: &[fn(Comparison) -> Comparison] = &[
+        |comparison| comparison,
+        |comparison| Comparison {
+            threads: 2,
+            ..comparison
+        },
+        |comparison| Comparison {
+            records: Some(&str::is_empty),
+            ..comparison
+        },
+        |comparison| Comparison {
+            options: CompareOptions::default()
+                .ignore_leading_whitespace()
+                .ignore_trailing_whitespace()
+                .collapse_inner_whitespace(),
+            ..comparison
+        },
+        |comparison| Comparison {
+            unordered: true,
+            ..comparison
+        },
+    ];
+
+    for variant in variants {
+        // Hunks of unordered comparisons describe sorted sides.
+        let unordered = variant(Comparison::new(&left, &right)).unordered;
+        let sorted = |lines: &[&'static str]| {
+            let mut lines = lines.to_vec();
+            if unordered {
+                lines.sort_unstable();
+            }
+            lines
+        };
+        let (left, right) = (sorted(&left), sorted(&right));
+        for context_radius in 0..4 {
+            for interhunk_lines in [None, Some(0), Some(1)].iter().copied() {
+                for semantic_cleanup in [false, true].iter().copied() {
+                    let comparison = variant(Comparison {
+                        context_radius,
+                        interhunk_lines,
+                        semantic_cleanup,
+                        ..Comparison::new(&left, &right)
+                    });
+                    check(&comparison, &left, &right);
+                }
+            }
+        }
+    }
+}
+
+/// Checks hunks and the patch of the comparison against texts, sorted in unordered mode.
+fn check(comparison: &Comparison, left: &[&str], right: &[&str]) {
+    let result = comparison.compare().expect("Comparison failed");
+    let describe = || {
+        format!(
+            "left: {:?}\nright: {:?}\n{:#?}\n{:#?}",
+            left, right, comparison, result
+        )
+    };
+    let same = |reconstructed: Option<Vec<&str>>, expected: &[&str]| {
+        reconstructed.is_some_and(|lines| {
+            lines.len() == expected.len()
+                && lines
+                    .iter()
+                    .zip(expected)
+                    .all(|(line, expected)| comparison.options.eq(line, expected))
+        })
+    };
+    let hunks = CompareResult {
+        unordered: false,
+        ..result.clone()
+    };
+    assert!(
+        same(hunks.reconstruct_right(left), right),
+        "Hunks don't reconstruct the right text.\n{}",
+        describe()
+    );
+    assert!(
+        same(hunks.reconstruct_left(right), left),
+        "Hunks don't reconstruct the left text.\n{}",
+        describe()
+    );
+
+    let mut set = PatchSet::default();
+    set.files
+        .push(FilePatch::new("left", "right", result.clone()));
+    let patch = set.patch(PatchOptions::default()).to_string();
+    let parsed = PatchSet::parse(&patch).expect("Rendered patch doesn't parse");
+    assert!(
+        same(parsed.files[0].reconstruct_right(left), right),
+        "Patch doesn't reconstruct the right text.\n{}\n{}",
+        patch,
+        describe()
+    );
+}
@@ -1,21 +1,27 @@
 use crate::Line;
+use std::ops::Range;
 
 /// Contains group of differing lines wrapped by sequences of lines common to both files.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Hunk<'a> {
     pub(crate) old_start: usize,
     pub(crate) new_start: usize,
     pub(crate) inserted: usize,
     pub(crate) removed: usize,
     pub(crate) lines: Vec<Line<'a>>,
+    /// Positions of the last lines of files without the new line at the end, followed by
+    /// `\ No newline at end of file`. Known only for hunks of a parsed patch
+    pub(crate) last_lines: (Option<usize>, Option<usize>),
 }
 
 impl<'a> Hunk<'a> {
-    /// Old/left start line of a hunk
+    /// Old/left start line of a hunk, counted from 0. For hunks without any line on the left side
+    /// it is the position where the lines were inserted
     pub fn old_start(&self) -> usize {
         self.old_start
     }
-    /// New/right start line of a hunk
+    /// New/right start line of a hunk, counted from 0. For hunks without any line on the right
+    /// side it is the position where the lines were removed
     pub fn new_start(&self) -> usize {
         self.new_start
     }
@@ -31,4 +37,51 @@ impl<'a> Hunk<'a> {
     pub fn lines(&self) -> &[Line<'a>] {
         &self.lines
     }
+
+    /// Moves the hunk and all its lines by offsets of both files.
+    pub(crate) fn shift(&mut self, old: usize, new: usize) {
+        self.old_start += old;
+        self.new_start += new;
+        for line in &mut self.lines {
+            line.shift(old, new);
+        }
+        let (left_last, right_last) = &mut self.last_lines;
+        *left_last = left_last.map(|pos| pos + old);
+        *right_last = right_last.map(|pos| pos + new);
+    }
+
+    /// Byte range spanning all lines of the hunk found in the `original` buffer, see
+    /// [`Line::byte_range`](struct.Line.html#method.byte_range). Returns `None` if no line comes
+    /// from it.
+    pub fn byte_range(&self, original: &str) -> Option<Range<usize>> {
+        let mut ranges = self
+            .lines
+            .iter()
+            .filter_map(|line| line.byte_range(original));
+        let first = ranges.next()?;
+        Some(ranges.fold(first, |acc, range| {
+            acc.start.min(range.start)..acc.end.max(range.end)
+        }))
+    }
+
+    /// Header of the hunk in [Unified Format](https://www.gnu.org/software/diffutils/manual/html_node/Detailed-Unified.html),
+    /// e.g. `@@ -1,3 +1,4 @@`. Line numbers are moved by the `offset`, which is usually 1.
+    ///
+    /// Like in GNU diff, the count is omitted for single-line ranges, and empty ranges (pure
+    /// insertions and deletions without context) point to the line preceding them.
+    pub fn header(&self, offset: usize) -> String {
+        format!(
+            "@@ -{} +{} @@",
+            header_range(self.old_start, self.removed, offset),
+            header_range(self.new_start, self.inserted, offset)
+        )
+    }
+}
+
+fn header_range(start: usize, count: usize, offset: usize) -> String {
+    match count {
+        0 => format!("{},0", (start + offset).saturating_sub(1)),
+        1 => format!("{}", start + offset),
+        _ => format!("{},{}", start + offset, count),
+    }
 }
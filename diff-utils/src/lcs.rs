@@ -0,0 +1,37 @@
+use crate::line::{occurrences, Occurrence};
+use crate::Line;
+use std::io;
+
+/// Collects only the lines common to both files.
+#[derive(Debug)]
+pub(crate) struct Lcs<'t, 'a> {
+    pub(crate) text1: &'t [&'a str],
+    pub(crate) occurrences1: Vec<Occurrence>,
+    pub(crate) result: Vec<Line<'a>>,
+}
+
+impl<'t, 'a> Lcs<'t, 'a> {
+    pub fn new(text1: &'t [&'a str]) -> Self {
+        Self {
+            text1,
+            occurrences1: occurrences(text1),
+            result: Vec::new(),
+        }
+    }
+
+    pub fn result(self) -> Vec<Line<'a>> {
+        self.result
+    }
+}
+
+impl<'t, 'a> diffs::Diff for Lcs<'t, 'a> {
+    type Error = io::Error;
+
+    fn equal(&mut self, old: usize, new: usize, len: usize) -> Result<(), Self::Error> {
+        for (i, j) in (old..old + len).zip(new..new + len) {
+            self.result
+                .push(Line::unchanged(i, j, self.text1[i]).with_occurrence(self.occurrences1[i]));
+        }
+        Ok(())
+    }
+}
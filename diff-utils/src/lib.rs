@@ -25,6 +25,9 @@
 //! let result = Comparison::new(&["foo", "bar"], &["foo", "foo"]).compare().expect("Comparison failed");
 //! ```
 //!
+//! Or with [`compare_infallible`](struct.Comparison.html#method.compare_infallible), which never
+//! returns an error and saves you the `expect`.
+//!
 //! The result can be either printed (with `display` feature - see [`display`](struct.CompareResult#method.display) method),
 //! used to generate patch (with `patch` feature - see [`patch`](struct.CompareResult#method.patch) method), or to simply
 //! check if two files were equal:
@@ -38,33 +41,88 @@
 //! ```
 //!
 //! # Features:
-//! * `display` - to pretty print hunks in the console,
-//! * `patch` to generate patch files
+//! * `display` - to pretty print hunks in the console, colored with the `colored` crate by default,
+//! * `display-plain` - `display` styled by a pluggable `Painter` only, without the `colored` dependency,
+//! * `colored` - painter based on the `colored` crate, used by default when enabled,
+//! * `patch` to generate patch files, also for multiple files at once (see `PatchSet`),
+//! * `patch-chrono` - `patch` with formatting of `chrono` dates in file headers,
+//! * `fuzz` - helpers for fuzzing, used by `cargo fuzz` targets in `fuzz/`, with `patch`,
+//! * `perf-test` - corpora for `cargo bench` and the perf regression test with time budgets,
+//!   with `criterion` for the benchmarks, so regular builds never compile it
+//! * `simd` - line splitting with `memchr`, for large inputs
+//! * `unicode` - splitting of changed lines into grapheme clusters with `unicode-segmentation`,
+//!   instead of an approximation covering common combining marks and emoji
 
+mod apply;
+mod classify;
+mod cleanup;
+mod compare_options;
 mod context;
 mod hunk;
+mod lcs;
 mod line;
+mod multi;
+mod newline;
+mod ops;
+mod parallel;
 mod processor;
+mod records;
+#[cfg(any(feature = "display-plain", feature = "patch"))]
+mod render;
 
-#[cfg(feature = "display")]
+#[cfg(feature = "display-plain")]
 mod display;
 
 #[cfg(feature = "patch")]
 mod patch;
 
+#[cfg(feature = "patch")]
+mod patch_set;
+
+#[cfg(feature = "fuzz")]
+#[doc(hidden)]
+pub mod fuzz;
+
+#[cfg(feature = "perf-test")]
+#[doc(hidden)]
+pub mod perf;
+
+use crate::apply::Direction;
 use crate::context::Context;
+use crate::lcs::Lcs;
+use crate::ops::Recorder;
 use crate::processor::Processor;
+use std::borrow::Cow;
+use std::fmt;
 use std::io;
+use std::ops::Range;
 
+pub use crate::classify::{
+    Classifier, CommentOnly, HunkClass, Reorder, WhitespaceOnly, DEFAULT_CLASSIFIERS,
+};
+pub use crate::compare_options::CompareOptions;
 pub use crate::hunk::Hunk;
-pub use crate::line::{Line, LineKind};
+pub use crate::line::{Line, LineKind, Occurrence};
+pub use crate::multi::MultiCompareResult;
+pub use crate::newline::Newline;
+#[cfg(any(feature = "display-plain", feature = "patch"))]
+pub use crate::render::RenderOptions;
 
-#[cfg(feature = "display")]
-pub use crate::display::DisplayOptions;
+#[cfg(feature = "display-plain")]
+pub use crate::display::{display_width, Ansi, DisplayOptions, Painter, Plain, Severity, Style};
+
+#[cfg(all(feature = "display-plain", feature = "colored"))]
+pub use crate::display::Colored;
 
 #[cfg(feature = "patch")]
 pub use crate::patch::PatchOptions;
 
+#[cfg(feature = "patch-chrono")]
+pub use crate::patch::patch_timestamp;
+
+#[cfg(feature = "patch")]
+pub use crate::patch_set::{FilePatch, PatchSet};
+
 /// Main structure used to compare two slices of (in most cases) files.
 /// It performs `Patience` diff algorithm.
 ///
@@ -73,7 +131,6 @@ pub use crate::patch::PatchOptions;
 /// use diff_utils::Comparison;
 /// let result = Comparison::new(&["foo", "bar"], &["foo", "foo"]).compare().expect("Comparison failed");
 /// ```
-#[derive(Debug)]
 pub struct Comparison<'a> {
     /// Left/old file slice
     pub left: &'a [&'a str],
@@ -81,6 +138,119 @@ pub struct Comparison<'a> {
     pub right: &'a [&'a str],
     /// Context radius. Number of equal lines attached to each hunk before and after. Default: 3
     pub context_radius: usize,
+    /// Maximal number of unchanged lines between two changes for which they are still shown in
+    /// one hunk. Lower values keep hunks strictly separate, higher ones merge nearby hunks into
+    /// one. Leading context of a hunk is cut where the previous hunk ends, so hunks never
+    /// overlap. Default: `None` - twice the `context_radius`, so contexts of neighbouring hunks
+    /// are never cut, like in GNU diff.
+    pub interhunk_lines: Option<usize>,
+    /// Shift boundaries of inserted/removed blocks to align with blank lines and indentation,
+    /// which gives more human-meaningful hunks for code and config files. Of equally good
+    /// positions the earliest one is chosen, so the result is the same on every platform.
+    /// Default: false
+    pub semantic_cleanup: bool,
+    /// Record mode. Lines are grouped into records separated by lines for which the function
+    /// returns `true`, e.g. `Some(&|line| line.trim().is_empty())` for paragraphs. Whole records
+    /// are compared first, and only the changed ones are compared line by line, which avoids
+    /// fragmented hunks in paragraph-structured documents. Default: `None` - line by line
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let separator = String::from("---");
+    /// let is_separator = |line: &str| line == separator;
+    /// let left = ["a", "b", "---", "c"];
+    /// let right = ["c", "---", "a", "b"];
+    /// let result = Comparison {
+    ///     records: Some(&is_separator),
+    ///     ..Comparison::new(&left, &right)
+    /// }
+    /// .compare_infallible();
+    /// assert!(result.verify(&left, &right));
+    /// ```
+    pub records: Option<&'a (dyn Fn(&str) -> bool + Sync)>,
+    /// Number of threads comparing huge files in parallel. Files are split in chunks at lines
+    /// unique to both of them, the same ones patience algorithm matches first, so the result
+    /// rarely differs from a single-threaded one. Ignored in record mode. Default: 1
+    pub threads: usize,
+    /// Restricts the comparison to the given ranges of left and right lines, e.g. to matching
+    /// regions of two files. Positions in the result still count from the start of whole
+    /// slices. Ranges exceeding slices are truncated. Default: `None` - whole slices
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["header", "foo", "bar", "footer"];
+    /// let right = ["other header", "foo", "baz", "other footer"];
+    /// let result = Comparison {
+    ///     ranges: Some((1..3, 1..3)),
+    ///     ..Comparison::new(&left, &right)
+    /// }
+    /// .compare_infallible();
+    /// assert_eq!(result.hunks().len(), 1);
+    /// assert_eq!(result.hunks()[0].old_start(), 1);
+    /// ```
+    pub ranges: Option<(Range<usize>, Range<usize>)>,
+    /// Compares lines regardless of their order, like multisets, e.g. for dumps of hash maps.
+    /// Both sides are sorted before comparison, so only lines missing on one side or extra on
+    /// the other are reported. Lines keep their positions in the compared slices, so e.g.
+    /// [`Line::metadata`](struct.Line.html#method.metadata) works, but hunks follow the sorted
+    /// sides, so they can't be applied on the slices, see
+    /// [`CompareResult::verify`](struct.CompareResult.html#method.verify). Default: false
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let comparison = Comparison {
+    ///     unordered: true,
+    ///     ..Comparison::new(&["b", "a", "c"], &["c", "b", "a", "d"])
+    /// };
+    /// assert!(!comparison.are_equal());
+    ///
+    /// let result = comparison.compare_infallible();
+    /// let changed = result.hunks()[0].lines().iter().filter(|line| line.kind().is_change());
+    /// assert_eq!(changed.map(|line| line.inner()).collect::<Vec<_>>(), vec!["d"]);
+    /// ```
+    pub unordered: bool,
+    /// Whitespace differences ignored when lines are compared, see
+    /// [`CompareOptions`](struct.CompareOptions.html). Default: every line compared as it is
+    pub options: CompareOptions,
+    /// Upper bound of lines stored in the result, e.g. on CI runners with little memory. The edit
+    /// script is abandoned as soon as it changes more lines than that, and hunks are sized from
+    /// it before any of their lines is stored, so the comparison fails with `io::Error` instead
+    /// of building a bigger result. It doesn't bound memory used by the diff algorithm itself,
+    /// nor normalized copies of lines made for [`options`](#structfield.options), nor records
+    /// and chunks compared in [`records`](#structfield.records) and
+    /// [`threads`](#structfield.threads) modes. Default: `None` - no limit
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let comparison = Comparison {
+    ///     max_lines: Some(2),
+    ///     ..Comparison::new(&["foo", "bar"], &["baz", "qux"])
+    /// };
+    /// assert!(comparison.compare().is_err());
+    /// ```
+    pub max_lines: Option<usize>,
+}
+
+impl fmt::Debug for Comparison<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Comparison")
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .field("context_radius", &self.context_radius)
+            .field("interhunk_lines", &self.interhunk_lines)
+            .field("semantic_cleanup", &self.semantic_cleanup)
+            .field("records", &self.records.is_some())
+            .field("threads", &self.threads)
+            .field("ranges", &self.ranges)
+            .field("unordered", &self.unordered)
+            .field("options", &self.options)
+            .field("max_lines", &self.max_lines)
+            .finish()
+    }
 }
 
 impl<'a> Comparison<'a> {
@@ -90,37 +260,273 @@ impl<'a> Comparison<'a> {
             left,
             right,
             context_radius: 3,
+            interhunk_lines: None,
+            semantic_cleanup: false,
+            records: None,
+            threads: 1,
+            ranges: None,
+            unordered: false,
+            options: CompareOptions::default(),
+            max_lines: None,
+        }
+    }
+
+    /// Left and right lines within [`ranges`](#structfield.ranges), sorted in
+    /// [`unordered`](#structfield.unordered) mode.
+    fn restricted(&self) -> Restricted<'a> {
+        let restrict = |lines: &'a [&'a str], range: &Range<usize>| {
+            let start = range.start.min(lines.len());
+            let end = range.end.clamp(start, lines.len());
+            (&lines[start..end], start)
+        };
+        let (left, right, old, new) = match &self.ranges {
+            Some((left, right)) => {
+                let (left, old) = restrict(self.left, left);
+                let (right, new) = restrict(self.right, right);
+                (left, right, old, new)
+            }
+            None => (self.left, self.right, 0, 0),
+        };
+        let sort = |lines: &'a [&'a str]| {
+            let mut order = (0..lines.len()).collect::<Vec<_>>();
+            match self.options.is_exact() {
+                true => order.sort_by_key(|i| lines[*i]),
+                false => order.sort_by_cached_key(|i| self.options.normalize(lines[*i])),
+            }
+            let sorted = order.iter().map(|i| lines[*i]).collect::<Vec<_>>();
+            (Cow::Owned(sorted), order)
+        };
+        match self.unordered {
+            true => {
+                let (left, left_order) = sort(left);
+                let (right, right_order) = sort(right);
+                Restricted {
+                    left,
+                    right,
+                    old,
+                    new,
+                    order: Some((left_order, right_order)),
+                }
+            }
+            false => Restricted {
+                left: Cow::Borrowed(left),
+                right: Cow::Borrowed(right),
+                old,
+                new,
+                order: None,
+            },
         }
     }
 
     /// Perform comparision
     ///
     /// # Errors
-    /// In case of any errors in patience algorithm it may return `io::Error`.
+    /// In case of any errors in patience algorithm it may return `io::Error`, also if the result
+    /// exceeds [`max_lines`](#structfield.max_lines).
     pub fn compare(&self) -> io::Result<CompareResult<'a>> {
-        let mut processor = Processor::new(self.left, self.right, self.context_radius);
+        let restricted = self.restricted();
+        let (left, right) = (&*restricted.left, &*restricted.right);
+        let interhunk_lines = self.interhunk_lines.unwrap_or(self.context_radius * 2);
+        let mut processor = Processor::new(left, right, self.context_radius, interhunk_lines);
+        processor.max_lines = self.max_lines;
+        processor.recorder.max_changed = self.max_lines;
         {
             let mut replace = diffs::Replace::new(&mut processor);
-            diffs::patience::diff(
-                &mut replace,
-                self.left,
-                0,
-                self.left.len(),
-                self.right,
-                0,
-                self.right.len(),
-            )?;
+            if !self.options.is_exact() {
+                // Edit script of normalized lines is replayed on the original ones.
+                let left = self.options.normalize_all(left);
+                let right = self.options.normalize_all(right);
+                let left: Vec<&str> = left.iter().map(AsRef::as_ref).collect();
+                let right: Vec<&str> = right.iter().map(AsRef::as_ref).collect();
+                ops::replay(&self.ops(&left, &right)?, &mut replace)?;
+            } else if self.semantic_cleanup || self.records.is_some() || self.threads > 1 {
+                ops::replay(&self.ops(left, right)?, &mut replace)?;
+            } else {
+                diffs::patience::diff(&mut replace, left, 0, left.len(), right, 0, right.len())?;
+            }
+        }
+        let mut hunks = processor.result();
+        for hunk in &mut hunks {
+            if let Some((left_order, right_order)) = &restricted.order {
+                for line in &mut hunk.lines {
+                    line.reorder(left_order, right_order);
+                }
+            }
+            hunk.shift(restricted.old, restricted.new);
         }
         Ok(CompareResult {
-            hunks: processor.result(),
+            hunks,
+            left_len: self.left.len(),
+            right_len: self.right.len(),
+            unordered: self.unordered,
+            interhunk_lines: self.interhunk_lines,
         })
     }
+
+    /// Edit script in record mode, in parallel or with the semantic cleanup, if requested.
+    fn ops(&self, left: &[&str], right: &[&str]) -> io::Result<Vec<ops::Op>> {
+        let mut recorder = Recorder::with_max_changed(self.max_lines);
+        match self.records {
+            Some(is_separator) => records::diff(&mut recorder, left, right, is_separator)?,
+            None if self.threads > 1 => parallel::diff(&mut recorder, left, right, self.threads)?,
+            None => {
+                diffs::patience::diff(&mut recorder, left, 0, left.len(), right, 0, right.len())?
+            }
+        }
+        let mut ops = recorder.result();
+        if self.semantic_cleanup {
+            ops = cleanup::semantic(ops, left, right);
+        }
+        Ok(ops)
+    }
+
+    /// Same as [`compare`](struct.Comparison.html#method.compare), but without `io::Result`.
+    /// Comparison happens entirely in memory and none of its steps can fail, the error type
+    /// comes only from the `diffs` crate interface.
+    ///
+    /// # Panics
+    /// If the result exceeds [`max_lines`](#structfield.max_lines).
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let result = Comparison::new(&["foo", "bar"], &["foo", "foo"]).compare_infallible();
+    /// assert_eq!(result.hunks().len(), 1);
+    /// ```
+    pub fn compare_infallible(&self) -> CompareResult<'a> {
+        self.compare()
+            .unwrap_or_else(|e| panic!("In-memory comparison failed: {}", e))
+    }
+
+    /// Checks if both slices are equal, line by line, without running the diff algorithm at all.
+    /// It stops at the first mismatch, so it is much cheaper than checking if the result of
+    /// [`compare`](struct.Comparison.html#method.compare) is empty.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// assert!(Comparison::new(&["foo", "bar"], &["foo", "bar"]).are_equal());
+    /// assert!(!Comparison::new(&["foo", "bar"], &["foo"]).are_equal());
+    /// ```
+    pub fn are_equal(&self) -> bool {
+        let Restricted { left, right, .. } = self.restricted();
+        left.len() == right.len()
+            && left
+                .iter()
+                .zip(right.iter())
+                .all(|(left, right)| self.options.eq(left, right))
+    }
+
+    /// Finds the first differing pair of lines by simple iteration, without running the diff
+    /// algorithm and building hunks. It is a cheap check for hot paths which only need to know
+    /// whether and where both sides start to differ.
+    ///
+    /// Returns `None` if slices are equal. Otherwise the first line is
+    /// [`Removed`](enum.LineKind.html#variant.Removed) and the second one is
+    /// [`Inserted`](enum.LineKind.html#variant.Inserted), both at the same position, and any of
+    /// them is missing if its side ends there.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let (left, right) = Comparison::new(&["foo", "bar"], &["foo", "baz", "qux"])
+    ///     .first_difference()
+    ///     .expect("Slices differ");
+    /// assert_eq!(left.map(|line| (line.old_pos(), line.inner())), Some((Some(1), "bar")));
+    /// assert_eq!(right.map(|line| (line.new_pos(), line.inner())), Some((Some(1), "baz")));
+    ///
+    /// assert!(Comparison::new(&["foo"], &["foo"]).first_difference().is_none());
+    /// ```
+    pub fn first_difference(&self) -> Option<(Option<Line<'a>>, Option<Line<'a>>)> {
+        let restricted = self.restricted();
+        let (left, right) = (&*restricted.left, &*restricted.right);
+        let common = left
+            .iter()
+            .zip(right)
+            .take_while(|(left, right)| self.options.eq(left, right))
+            .count();
+        let left = left
+            .get(common)
+            .map(|line| restricted.locate(Line::remove(common, line)));
+        let right = right
+            .get(common)
+            .map(|line| restricted.locate(Line::insert(common, line)));
+        match (left, right) {
+            (None, None) => None,
+            difference => Some(difference),
+        }
+    }
+
+    /// Returns the longest sequence of lines common to both slices, in order, with their positions
+    /// in both of them. Every returned line is [`Unchanged`](enum.LineKind.html#variant.Unchanged).
+    /// It is computed with Myers' algorithm, so it may be longer than unchanged lines of
+    /// [`compare`](#method.compare), which follows patience matching of unique lines.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let common = Comparison::new(&["foo", "noise", "bar"], &["foo", "bar", "more noise"])
+    ///     .lcs()
+    ///     .expect("Comparison failed");
+    ///
+    /// let common = common.iter().map(|line| (line.old_pos(), line.new_pos(), line.inner())).collect::<Vec<_>>();
+    /// assert_eq!(common, vec![(Some(0), Some(0), "foo"), (Some(2), Some(1), "bar")]);
+    /// ```
+    ///
+    /// # Errors
+    /// In case of any errors in patience algorithm it may return `io::Error`.
+    pub fn lcs(&self) -> io::Result<Vec<Line<'a>>> {
+        let restricted = self.restricted();
+        let (left, right) = (&*restricted.left, &*restricted.right);
+        let mut lcs = Lcs::new(left);
+        if self.options.is_exact() {
+            diffs::myers::diff(&mut lcs, left, 0, left.len(), right, 0, right.len())?;
+        } else {
+            let left = self.options.normalize_all(left);
+            let right = self.options.normalize_all(right);
+            diffs::myers::diff(&mut lcs, &left, 0, left.len(), &right, 0, right.len())?;
+        }
+        Ok(lcs
+            .result()
+            .into_iter()
+            .map(|line| restricted.locate(line))
+            .collect())
+    }
+}
+
+/// Lines actually compared, with the way back to positions in whole slices of the comparison.
+struct Restricted<'a> {
+    left: Cow<'a, [&'a str]>,
+    right: Cow<'a, [&'a str]>,
+    /// Offsets of both sides, in [`ranges`](struct.Comparison.html#structfield.ranges) mode
+    old: usize,
+    new: usize,
+    /// Original positions of sorted lines of both sides, in
+    /// [`unordered`](struct.Comparison.html#structfield.unordered) mode
+    order: Option<(Vec<usize>, Vec<usize>)>,
+}
+
+impl Restricted<'_> {
+    /// Moves positions of the line from compared lines to whole slices.
+    fn locate<'a>(&self, mut line: Line<'a>) -> Line<'a> {
+        if let Some((left_order, right_order)) = &self.order {
+            line.reorder(left_order, right_order);
+        }
+        line.shift(self.old, self.new);
+        line
+    }
 }
 
 /// The actual result of a comparison. It contains the list of the hunks with line differences.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CompareResult<'a> {
     pub(crate) hunks: Vec<Hunk<'a>>,
+    pub(crate) left_len: usize,
+    pub(crate) right_len: usize,
+    /// Hunks describe sorted sides, so they can't be applied on the compared slices
+    pub(crate) unordered: bool,
+    /// Gap setting of the comparison, kept for rebuilding hunks with another radius
+    pub(crate) interhunk_lines: Option<usize>,
 }
 
 impl<'a> CompareResult<'a> {
@@ -133,6 +539,171 @@ impl<'a> CompareResult<'a> {
     pub fn hunks(&self) -> &[Hunk<'a>] {
         &self.hunks
     }
+
+    /// Checks if hunks really describe the difference between `left` and `right`, which means
+    /// applying them on `left` gives back `right`. It is mostly useful for testing tools built on
+    /// top of diff-utils and for fuzzing. Hunks of [`unordered`](struct.Comparison.html#structfield.unordered)
+    /// comparisons describe sorted sides, so they are never verified.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["foo", "bar"];
+    /// let right = ["foo", "baz"];
+    /// let result = Comparison::new(&left, &right).compare().unwrap();
+    /// assert!(result.verify(&left, &right));
+    /// assert!(!result.verify(&right, &left));
+    /// ```
+    pub fn verify(&self, left: &[&str], right: &[&str]) -> bool {
+        self.reconstruct_right(left)
+            .is_some_and(|applied| applied == right)
+    }
+
+    /// Rebuilds the right/new file by applying hunks on the `left` one.
+    /// Returns `None` if hunks don't match the `left` file, or they come from an
+    /// [`unordered`](struct.Comparison.html#structfield.unordered) comparison.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["foo", "bar"];
+    /// let right = ["foo", "baz"];
+    /// let result = Comparison::new(&left, &right).compare().unwrap();
+    /// assert_eq!(result.reconstruct_right(&left), Some(vec!["foo", "baz"]));
+    /// ```
+    pub fn reconstruct_right(&self, left: &[&'a str]) -> Option<Vec<&'a str>> {
+        match self.unordered {
+            true => None,
+            false => apply::apply(&self.hunks, left, Direction::Forward),
+        }
+    }
+
+    /// Rebuilds the left/old file by reverting hunks on the `right` one.
+    /// Returns `None` if hunks don't match the `right` file, or they come from an
+    /// [`unordered`](struct.Comparison.html#structfield.unordered) comparison.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["foo", "bar"];
+    /// let right = ["foo", "baz"];
+    /// let result = Comparison::new(&left, &right).compare().unwrap();
+    /// assert_eq!(result.reconstruct_left(&right), Some(vec!["foo", "bar"]));
+    /// ```
+    pub fn reconstruct_left(&self, right: &[&'a str]) -> Option<Vec<&'a str>> {
+        match self.unordered {
+            true => None,
+            false => apply::apply(&self.hunks, right, Direction::Backward),
+        }
+    }
+
+    /// Rebuilds hunks with a different context radius, e.g. to generate a patch with 3 lines of
+    /// context while the assertion shows none. `left` and `right` have to be the compared slices,
+    /// because new context lines come from them. Hunks closer than the
+    /// [`interhunk_lines`](struct.Comparison.html#structfield.interhunk_lines) of the comparison
+    /// are merged, twice the new radius if it wasn't set. Returns `None` if the slice lengths
+    /// don't match the compared ones, or hunks come from an
+    /// [`unordered`](struct.Comparison.html#structfield.unordered) comparison.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["a", "b", "c", "d"];
+    /// let right = ["a", "b", "C", "d"];
+    /// let result = Comparison { context_radius: 0, ..Comparison::new(&left, &right) }.compare_infallible();
+    /// assert_eq!(result.hunks()[0].lines().len(), 2);
+    ///
+    /// let wider = result.with_context(&left, &right, 3).unwrap();
+    /// assert_eq!(wider.hunks()[0].lines().len(), 5);
+    /// assert_eq!(result.with_context(&left[1..], &right, 3), None);
+    /// ```
+    pub fn with_context(
+        &self,
+        left: &'a [&'a str],
+        right: &'a [&'a str],
+        context_radius: usize,
+    ) -> Option<CompareResult<'a>> {
+        if self.unordered || left.len() != self.left_len || right.len() != self.right_len {
+            return None;
+        }
+        let interhunk_lines = self.interhunk_lines.unwrap_or(context_radius * 2);
+        let mut processor = Processor::new(left, right, context_radius, interhunk_lines);
+        ops::from_hunks(&self.hunks, left.len(), right.len())
+            .and_then(|ops| ops::replay(&ops, &mut diffs::Replace::new(&mut processor)))
+            .ok()?;
+        Some(CompareResult {
+            hunks: processor.result(),
+            left_len: left.len(),
+            right_len: right.len(),
+            unordered: false,
+            interhunk_lines: self.interhunk_lines,
+        })
+    }
+
+    /// Splits the right/new file into `buckets` segments of equal size and counts how many changed
+    /// lines fall into each of them. Removed lines are counted at the place in the new file where
+    /// they used to be. It is handy for rendering a minimap or a scrollbar heatmap.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["foo", "bar", "baz", "qux"];
+    /// let right = ["foo", "bar", "baz", "quux"];
+    /// let result = Comparison::new(&left, &right).compare().unwrap();
+    /// assert_eq!(result.density(2), vec![0, 2]);
+    /// ```
+    pub fn density(&self, buckets: usize) -> Vec<usize> {
+        let mut density = vec![0; buckets];
+        if buckets == 0 {
+            return density;
+        }
+
+        let len = std::cmp::max(self.right_len, 1);
+        for hunk in &self.hunks {
+            let mut pos = hunk.new_start;
+            for line in &hunk.lines {
+                if line.kind != LineKind::Unchanged {
+                    let bucket = std::cmp::min(pos * buckets / len, buckets - 1);
+                    density[bucket] += 1;
+                }
+                if line.kind != LineKind::Removed && line.kind != LineKind::ReplaceRemoved {
+                    pos += 1;
+                }
+            }
+        }
+        density
+    }
+}
+
+/// Short summary, like `3 hunks (+10/-4)`, handy for logging. To print the whole diff use
+/// [`display`](struct.CompareResult#method.display) method instead.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::Comparison;
+/// let result = Comparison::new(&["foo", "bar"], &["foo", "baz", "qux"]).compare().unwrap();
+/// assert_eq!(result.to_string(), "1 hunk (+2/-1)");
+/// ```
+impl fmt::Display for CompareResult<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let kinds = || {
+            self.hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .map(|line| line.kind)
+        };
+        let inserted = kinds().filter(|kind| kind.is_insertion()).count();
+        let removed = kinds().filter(|kind| kind.is_deletion()).count();
+        let plural = if self.hunks.len() == 1 { "" } else { "s" };
+        write!(
+            f,
+            "{} hunk{} (+{}/-{})",
+            self.hunks.len(),
+            plural,
+            inserted,
+            removed
+        )
+    }
 }
 
 /// Performs diff and returns list of hunks.
@@ -146,11 +717,10 @@ pub fn diff_hunks<'a>(
     left: &'a [&'a str],
     right: &'a [&'a str],
     context_radius: usize,
-) -> std::io::Result<Vec<Hunk<'a>>> {
+) -> io::Result<Vec<Hunk<'a>>> {
     let comparison = Comparison {
-        left,
-        right,
         context_radius,
+        ..Comparison::new(left, right)
     }
     .compare()?;
 
@@ -158,17 +728,13 @@ pub fn diff_hunks<'a>(
 }
 
 /// Performs diff on two files and returns formatted display.
-#[cfg(feature = "display")]
+#[cfg(feature = "display-plain")]
 #[deprecated(
     since = "0.3.0",
     note = "Instead you should use `Comparison::new(..).compare(..)`"
 )]
 #[allow(deprecated)]
-pub fn diff(
-    text1: &[String],
-    text2: &[String],
-    context_radius: usize,
-) -> std::io::Result<Vec<String>> {
+pub fn diff(text1: &[String], text2: &[String], context_radius: usize) -> io::Result<Vec<String>> {
     let left = text1.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
     let right = text2.iter().map(|s| s.as_ref()).collect::<Vec<&str>>();
 
@@ -214,8 +780,6 @@ mod tests {
         #[test_case(TEST_2)]
         #[test_case(TEST_3)]
         fn test(TestCase { a, b }: TestCase) {
-            colored::control::set_override(false);
-
             let left: Vec<&str> = a.lines().collect();
             let right: Vec<&str> = b.lines().collect();
             let result = Comparison::new(&left, &right).compare().expect("hunks");
@@ -224,10 +788,881 @@ mod tests {
                 let hunks = result
                     .hunks
                     .iter()
-                    .map(|s| format!("{}\n", s.display(Default::default())))
+                    .map(|s| format!("{}\n", s.display_plain()))
                     .join("\n");
 
-                insta::assert_display_snapshot!(hunks);
+                insta::assert_snapshot!(hunks);
+            }
+        }
+    }
+
+    mod density {
+        use super::*;
+
+        #[test]
+        fn scattered_changes() {
+            let left = ["a", "b", "c", "d", "e", "f", "g", "h"];
+            let right = ["A", "b", "c", "d", "e", "f", "g", "H"];
+            let result = Comparison::new(&left, &right).compare().expect("hunks");
+
+            assert_eq!(result.density(4), vec![2, 0, 0, 2]);
+            assert_eq!(result.density(1), vec![4]);
+            assert!(result.density(0).is_empty());
+        }
+
+        #[test]
+        fn removed_everything() {
+            let left = ["a", "b", "c"];
+            let right = [];
+            let result = Comparison::new(&left, &right).compare().expect("hunks");
+
+            assert_eq!(result.density(3), vec![3, 0, 0]);
+        }
+    }
+
+    mod first_difference {
+        use super::*;
+
+        type Side<'a> = Option<(usize, &'a str)>;
+
+        fn first_difference<'a>(
+            left: &'a [&'a str],
+            right: &'a [&'a str],
+        ) -> Option<(Side<'a>, Side<'a>)> {
+            Comparison::new(left, right)
+                .first_difference()
+                .map(|(removed, inserted)| {
+                    assert!(removed.iter().all(|line| line.kind() == LineKind::Removed));
+                    assert!(inserted
+                        .iter()
+                        .all(|line| line.kind() == LineKind::Inserted));
+                    (
+                        removed.map(|line| (line.old_pos().unwrap(), line.inner())),
+                        inserted.map(|line| (line.new_pos().unwrap(), line.inner())),
+                    )
+                })
+        }
+
+        #[test]
+        fn finds_first_pair() {
+            assert_eq!(first_difference(&[], &[]), None);
+            assert_eq!(first_difference(&["a", "b"], &["a", "b"]), None);
+            assert_eq!(
+                first_difference(&["a", "b", "c"], &["a", "x", "c"]),
+                Some((Some((1, "b")), Some((1, "x"))))
+            );
+            assert_eq!(
+                first_difference(&["a"], &["a", "b"]),
+                Some((None, Some((1, "b"))))
+            );
+            assert_eq!(
+                first_difference(&["a", "b"], &[]),
+                Some((Some((0, "a")), None))
+            );
+        }
+
+        #[test]
+        fn equality_matches_compare() {
+            let cases: &[(&[&str], &[&str])] = &[
+                (&[], &[]),
+                (&["a"], &["a"]),
+                (&["a"], &["a", ""]),
+                (&["a", "b"], &["b", "a"]),
+            ];
+            for (left, right) in cases {
+                let comparison = Comparison::new(left, right);
+                assert_eq!(
+                    comparison.are_equal(),
+                    comparison.compare_infallible().is_empty()
+                );
+                assert_eq!(
+                    comparison.are_equal(),
+                    comparison.first_difference().is_none()
+                );
+            }
+        }
+
+        #[test]
+        fn starts_where_first_hunk_changes() {
+            let left = ["a", "b", "c", "d"];
+            let right = ["a", "b", "x", "d", "e"];
+            let comparison = Comparison::new(&left, &right);
+            let first_change = comparison.compare_infallible().hunks()[0]
+                .lines()
+                .iter()
+                .find(|line| line.kind().is_change())
+                .and_then(|line| line.old_pos());
+            let (removed, _) = comparison.first_difference().unwrap();
+            assert_eq!(removed.and_then(|line| line.old_pos()), first_change);
+        }
+    }
+
+    mod with_context {
+        use super::*;
+
+        #[test]
+        fn same_as_compared_with_that_radius() {
+            let left = "a b c d e f g h i j k l".split(' ').collect::<Vec<_>>();
+            let cases = [
+                "a B c d e f g h i j K l",
+                "x a b c d e f g h i j k l y",
+                "a b c e f g h i j k l",
+                "",
+                "l k j i h g f e d c b a",
+            ];
+            for right in cases.iter() {
+                let right = right
+                    .split(' ')
+                    .filter(|s| !s.is_empty())
+                    .collect::<Vec<_>>();
+                let compare = |context_radius| {
+                    Comparison {
+                        context_radius,
+                        ..Comparison::new(&left, &right)
+                    }
+                    .compare_infallible()
+                };
+                for (from, to) in [(0, 3), (3, 0), (1, 5), (2, 2)].iter().copied() {
+                    assert_eq!(
+                        compare(from).with_context(&left, &right, to),
+                        Some(compare(to)),
+                        "{:?} from {} to {}",
+                        right,
+                        from,
+                        to
+                    );
+                }
+            }
+        }
+
+        #[test]
+        fn keeps_interhunk_lines() {
+            let left = "a b c d e f g h".split(' ').collect::<Vec<_>>();
+            let right = "A b c d e f g H".split(' ').collect::<Vec<_>>();
+            let compare = |context_radius| {
+                Comparison {
+                    context_radius,
+                    interhunk_lines: Some(0),
+                    ..Comparison::new(&left, &right)
+                }
+                .compare_infallible()
+            };
+            let wider = compare(0).with_context(&left, &right, 4);
+            assert_eq!(wider, Some(compare(4)));
+            assert_eq!(wider.map(|result| result.hunks.len()), Some(2));
+        }
+
+        #[test]
+        fn other_slices() {
+            let (left, right) = (["a", "b"], ["a", "c"]);
+            let result = Comparison::new(&left, &right).compare_infallible();
+            assert_eq!(result.with_context(&left[..1], &right, 3), None);
+            assert_eq!(result.with_context(&left, &["a", "c", "d"], 3), None);
+        }
+    }
+
+    mod ranges {
+        use super::*;
+
+        const LEFT: &[&str] = &["a", "b", "c", "d", "e", "f"];
+        const RIGHT: &[&str] = &["x", "b", "C", "d", "y", "z"];
+
+        fn restricted(left: Range<usize>, right: Range<usize>) -> Comparison<'static> {
+            Comparison {
+                ranges: Some((left, right)),
+                ..Comparison::new(LEFT, RIGHT)
+            }
+        }
+
+        #[test]
+        fn positions_count_from_whole_slices() {
+            let result = restricted(1..4, 1..4).compare().expect("hunks");
+            let hunks = result.hunks();
+            assert_eq!(hunks.len(), 1);
+            assert_eq!((hunks[0].old_start(), hunks[0].new_start()), (1, 1));
+            assert_eq!(
+                hunks[0]
+                    .lines()
+                    .iter()
+                    .map(|line| (line.old_pos(), line.new_pos(), line.inner()))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (Some(1), Some(1), "b"),
+                    (Some(2), Some(2), "c"),
+                    (Some(2), Some(2), "C"),
+                    (Some(3), Some(3), "d"),
+                ]
+            );
+            assert_eq!(
+                result.reconstruct_right(LEFT).unwrap()[..4],
+                ["a", "b", "C", "d"]
+            );
+        }
+
+        #[test]
+        fn different_offsets() {
+            let comparison = restricted(3..4, 1..2);
+            assert!(!comparison.are_equal());
+            let (left, right) = comparison.first_difference().unwrap();
+            assert_eq!(left.map(|line| line.old_pos()), Some(Some(3)));
+            assert_eq!(right.map(|line| line.new_pos()), Some(Some(1)));
+
+            let comparison = restricted(1..2, 1..2);
+            assert!(comparison.are_equal());
+            assert!(comparison.compare_infallible().is_empty());
+            let common = comparison.lcs().unwrap();
+            assert_eq!(
+                (common[0].old_pos(), common[0].new_pos()),
+                (Some(1), Some(1))
+            );
+        }
+
+        #[test]
+        fn truncated_ranges() {
+            assert!(restricted(4..100, 10..20).compare().unwrap().hunks()[0]
+                .lines()
+                .iter()
+                .all(|line| line.kind() == LineKind::Removed));
+            assert!(restricted(6..9, 9..10).are_equal());
+        }
+    }
+
+    mod whitespace {
+        use super::*;
+
+        #[test]
+        fn original_lines_are_reported() {
+            let left = ["fn foo() {", "  bar();  ", "}"];
+            let right = ["fn foo() {", "    bar();", "    baz();", "}"];
+            let comparison = Comparison {
+                options: CompareOptions::default()
+                    .ignore_leading_whitespace()
+                    .ignore_trailing_whitespace(),
+                ..Comparison::new(&left, &right)
+            };
+            assert!(!comparison.are_equal());
+            let (_, right_line) = comparison.first_difference().unwrap();
+            assert_eq!(right_line.map(|line| line.inner()), Some("    baz();"));
+
+            let result = comparison.compare_infallible();
+            let lines = result.hunks()[0]
+                .lines()
+                .iter()
+                .map(|line| (line.kind(), line.inner()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                lines,
+                vec![
+                    (LineKind::Unchanged, "fn foo() {"),
+                    (LineKind::Unchanged, "  bar();  "),
+                    (LineKind::Inserted, "    baz();"),
+                    (LineKind::Unchanged, "}"),
+                ]
+            );
+            assert_eq!(comparison.lcs().unwrap().len(), 3);
+        }
+
+        #[test]
+        fn with_other_modes() {
+            let left = ["a  b", "c", "d"];
+            let right = ["d", "a b", "c"];
+            let options = CompareOptions::default().collapse_inner_whitespace();
+            let comparison = Comparison {
+                options,
+                unordered: true,
+                ..Comparison::new(&left, &right)
+            };
+            assert!(comparison.are_equal());
+
+            let comparison = Comparison {
+                options,
+                semantic_cleanup: true,
+                ..Comparison::new(&left, &right)
+            };
+            let result = comparison.compare_infallible();
+            assert_eq!(
+                result.hunks()[0]
+                    .lines()
+                    .iter()
+                    .filter(|line| line.kind().is_change())
+                    .count(),
+                2
+            );
+        }
+    }
+
+    mod unordered {
+        use super::*;
+
+        fn unordered<'a>(left: &'a [&'a str], right: &'a [&'a str]) -> Comparison<'a> {
+            Comparison {
+                unordered: true,
+                ..Comparison::new(left, right)
+            }
+        }
+
+        #[test]
+        fn order_is_ignored() {
+            let comparison = unordered(&["b", "a", "b"], &["a", "b", "b"]);
+            assert!(comparison.are_equal());
+            assert!(comparison.first_difference().is_none());
+            assert!(comparison.compare_infallible().is_empty());
+        }
+
+        #[test]
+        fn only_missing_and_extra_lines() {
+            let result =
+                unordered(&["c", "a", "b", "a"], &["a", "d", "c", "b"]).compare_infallible();
+            let changed = result
+                .hunks()
+                .iter()
+                .flat_map(|hunk| hunk.lines())
+                .filter(|line| line.kind().is_change())
+                .map(|line| (line.kind(), line.inner()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                changed,
+                vec![(LineKind::Removed, "a"), (LineKind::Inserted, "d")]
+            );
+        }
+
+        #[test]
+        fn positions_in_compared_slices() {
+            let (left, right) = (["b", "a", "c"], ["c", "b", "a", "d"]);
+            let result = unordered(&left, &right).compare_infallible();
+            let (left_ids, right_ids) = ([10, 11, 12], [20, 21, 22, 23]);
+            let lines = result
+                .hunks()
+                .iter()
+                .flat_map(|hunk| hunk.lines())
+                .map(|line| (line.inner(), line.metadata(&left_ids, &right_ids)))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                lines,
+                vec![
+                    ("a", (Some(&11), Some(&22))),
+                    ("b", (Some(&10), Some(&21))),
+                    ("c", (Some(&12), Some(&20))),
+                    ("d", (None, Some(&23)))
+                ]
+            );
+
+            let first = unordered(&left, &["a", "d"]).first_difference();
+            assert_eq!(first.and_then(|(left, _)| left?.old_pos()), Some(0));
+        }
+
+        #[test]
+        fn hunks_not_applied() {
+            let (left, right) = (["b", "a"], ["a", "b", "c"]);
+            let result = unordered(&left, &right).compare_infallible();
+            assert!(!result.verify(&left, &right));
+            assert_eq!(result.reconstruct_right(&left), None);
+            assert_eq!(result.reconstruct_left(&right), None);
+            assert_eq!(result.with_context(&left, &right, 0), None);
+        }
+    }
+
+    mod max_lines {
+        use super::*;
+
+        const LEFT: &[&str] = &["a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l"];
+        const RIGHT: &[&str] = &["a", "B", "c", "d", "x", "e", "f", "g", "h", "i", "j", "L"];
+
+        fn limited(max_lines: Option<usize>) -> io::Result<CompareResult<'static>> {
+            Comparison {
+                context_radius: 1,
+                max_lines,
+                ..Comparison::new(LEFT, RIGHT)
+            }
+            .compare()
+        }
+
+        #[test]
+        fn hunks_allocated_at_once() {
+            let result = limited(None).expect("result");
+            assert_eq!(result.hunks().len(), 2);
+            for hunk in result.hunks() {
+                assert_eq!(hunk.lines.capacity(), hunk.lines.len());
+            }
+        }
+
+        #[test]
+        fn limit_of_stored_lines() {
+            let result = limited(None).expect("result");
+            let total = result.hunks().iter().map(|hunk| hunk.lines().len()).sum();
+            assert_eq!(limited(Some(total)).expect("result"), result);
+            let error = limited(Some(total - 1)).expect_err("limit exceeded");
+            assert_eq!(error.kind(), io::ErrorKind::Other);
+        }
+
+        #[test]
+        fn edit_script_abandoned_early() {
+            let left = (0..1_000).map(|i| i.to_string()).collect::<Vec<_>>();
+            let right = (0..1_000).map(|i| format!("{}!", i)).collect::<Vec<_>>();
+            let left = left.iter().map(String::as_str).collect::<Vec<_>>();
+            let right = right.iter().map(String::as_str).collect::<Vec<_>>();
+            let comparisons = [
+                Comparison::new(&left, &right),
+                Comparison {
+                    semantic_cleanup: true,
+                    ..Comparison::new(&left, &right)
+                },
+                Comparison {
+                    threads: 2,
+                    ..Comparison::new(&left, &right)
+                },
+            ];
+            for comparison in comparisons {
+                let error = Comparison {
+                    max_lines: Some(10),
+                    ..comparison
+                }
+                .compare()
+                .expect_err("limit exceeded");
+                assert!(
+                    error.to_string().starts_with("result of at least"),
+                    "{}",
+                    error
+                );
+            }
+        }
+    }
+
+    mod lcs {
+        use super::*;
+
+        #[test]
+        fn common_lines() {
+            let left = ["start", "a", "noise", "b", "end"];
+            let right = ["start", "other", "a", "b", "end", "trailing"];
+            let lcs = Comparison::new(&left, &right).lcs().expect("lcs");
+
+            let lcs = lcs
+                .iter()
+                .map(|line| (line.old_pos, line.new_pos, line.inner))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                lcs,
+                vec![
+                    (Some(0), Some(0), "start"),
+                    (Some(1), Some(2), "a"),
+                    (Some(3), Some(3), "b"),
+                    (Some(4), Some(4), "end"),
+                ]
+            );
+        }
+
+        #[test]
+        fn longer_than_patience_matching() {
+            let (left, right) = (["u", "a", "a", "a"], ["a", "a", "a", "u"]);
+            let comparison = Comparison::new(&left, &right);
+            let unchanged = comparison
+                .compare_infallible()
+                .hunks()
+                .iter()
+                .flat_map(|hunk| hunk.lines())
+                .filter(|line| !line.kind().is_change())
+                .count();
+            assert_eq!(unchanged, 1);
+
+            let lcs = comparison.lcs().expect("lcs");
+            let lcs = lcs.iter().map(|line| line.inner).collect::<Vec<_>>();
+            assert_eq!(lcs, vec!["a", "a", "a"]);
+        }
+
+        #[test]
+        fn nothing_in_common() {
+            let lcs = Comparison::new(&["a"], &["b"]).lcs().expect("lcs");
+            assert!(lcs.is_empty());
+        }
+    }
+
+    mod interhunk_lines {
+        use super::*;
+
+        const LEFT: &[&str] = &["a", "1", "2", "3", "b", "4", "5", "6", "7", "8", "9", "c"];
+        const RIGHT: &[&str] = &["A", "1", "2", "3", "B", "4", "5", "6", "7", "8", "9", "C"];
+
+        fn compare(interhunk_lines: Option<usize>) -> Vec<(usize, usize, usize, usize)> {
+            let result = Comparison {
+                context_radius: 1,
+                interhunk_lines,
+                ..Comparison::new(LEFT, RIGHT)
+            }
+            .compare()
+            .expect("hunks");
+
+            result
+                .hunks
+                .iter()
+                .map(|h| (h.old_start, h.removed, h.new_start, h.inserted))
+                .collect()
+        }
+
+        #[test]
+        fn default_splits_distant_changes() {
+            assert_eq!(
+                compare(None),
+                vec![(0, 2, 0, 2), (3, 3, 3, 3), (10, 2, 10, 2)]
+            );
+        }
+
+        #[test]
+        fn force_merging() {
+            assert_eq!(compare(Some(3)), vec![(0, 6, 0, 6), (10, 2, 10, 2)]);
+            assert_eq!(compare(Some(6)), vec![(0, 12, 0, 12)]);
+        }
+
+        #[test]
+        fn keep_separate() {
+            let result = Comparison {
+                context_radius: 2,
+                interhunk_lines: Some(0),
+                ..Comparison::new(LEFT, RIGHT)
+            }
+            .compare()
+            .expect("hunks");
+
+            let hunks = result
+                .hunks
+                .iter()
+                .map(|h| (h.old_start, h.removed, h.new_start, h.inserted))
+                .collect::<Vec<_>>();
+            // Leading context of the second hunk is cut where the first one ends.
+            assert_eq!(hunks, vec![(0, 3, 0, 3), (3, 4, 3, 4), (9, 3, 9, 3)]);
+            assert!(result.verify(LEFT, RIGHT));
+            assert_eq!(result.reconstruct_left(RIGHT).as_deref(), Some(LEFT));
+        }
+    }
+
+    mod positions {
+        use super::*;
+
+        fn positions(left: &[&str], right: &[&str], context_radius: usize) -> Vec<[usize; 4]> {
+            let result = Comparison {
+                context_radius,
+                ..Comparison::new(left, right)
+            }
+            .compare()
+            .expect("hunks");
+
+            let hunks = result.hunks();
+            // Distance between starts on both sides has to match the changes made so far.
+            let mut shift = 0isize;
+            for hunk in hunks {
+                assert_eq!(hunk.new_start() as isize - hunk.old_start() as isize, shift);
+                shift += hunk.inserted() as isize - hunk.removed() as isize;
+            }
+
+            hunks
+                .iter()
+                .map(|h| [h.old_start(), h.removed(), h.new_start(), h.inserted()])
+                .collect()
+        }
+
+        #[test]
+        fn removals_at_the_start() {
+            let left = [
+                "a", "b", "c", "d", "e", "f", "1", "2", "3", "4", "5", "6", "7", "x",
+            ];
+            let right = ["1", "2", "3", "4", "5", "6", "7", "y"];
+
+            assert_eq!(
+                positions(&left, &right, 0),
+                vec![[0, 6, 0, 0], [13, 1, 7, 1]]
+            );
+            assert_eq!(
+                positions(&left, &right, 1),
+                vec![[0, 7, 0, 1], [12, 2, 6, 2]]
+            );
+            assert_eq!(
+                positions(&left, &right, 3),
+                vec![[0, 9, 0, 3], [10, 4, 4, 4]]
+            );
+        }
+
+        #[test]
+        fn removals_everywhere() {
+            let left = ["a", "1", "b", "c", "2", "d", "e", "f", "3"];
+            let right = ["1", "2", "3"];
+
+            assert_eq!(
+                positions(&left, &right, 0),
+                vec![[0, 1, 0, 0], [2, 2, 1, 0], [5, 3, 2, 0]]
+            );
+            assert_eq!(positions(&left, &right, 1), vec![[0, 9, 0, 3]]);
+        }
+
+        #[test]
+        fn everything_removed() {
+            assert_eq!(positions(&["a", "b"], &[], 3), vec![[0, 2, 0, 0]]);
+            assert_eq!(positions(&[], &["a", "b"], 3), vec![[0, 0, 0, 2]]);
+        }
+    }
+
+    mod reconstruct {
+        use super::*;
+
+        const LEFT: &[&str] = &["a", "b", "c", "d", "e", "f", "g", "h", "i"];
+        const RIGHT: &[&str] = &["x", "a", "c", "D", "e", "f", "g", "h", "i", "y", "z"];
+
+        #[test]
+        fn both_ways() {
+            for context_radius in 0..4 {
+                let result = Comparison {
+                    context_radius,
+                    ..Comparison::new(LEFT, RIGHT)
+                }
+                .compare()
+                .expect("hunks");
+
+                assert_eq!(result.reconstruct_right(LEFT).as_deref(), Some(RIGHT));
+                assert_eq!(result.reconstruct_left(RIGHT).as_deref(), Some(LEFT));
+            }
+        }
+
+        #[test]
+        fn wrong_file() {
+            let result = Comparison::new(LEFT, RIGHT).compare().expect("hunks");
+
+            assert_eq!(result.reconstruct_right(RIGHT), None);
+            assert_eq!(result.reconstruct_left(LEFT), None);
+            assert_eq!(result.reconstruct_right(&LEFT[..3]), None);
+        }
+    }
+
+    mod equality {
+        use super::*;
+        use std::collections::HashSet;
+
+        #[test]
+        fn hunks_deduplicate() {
+            let left = ["a", "b", "c", "a", "b", "c"];
+            let right = ["a", "B", "c", "a", "B", "c"];
+            let result = Comparison {
+                context_radius: 0,
+                ..Comparison::new(&left, &right)
+            }
+            .compare()
+            .expect("hunks");
+
+            assert_eq!(
+                result,
+                Comparison {
+                    context_radius: 0,
+                    ..Comparison::new(&left, &right)
+                }
+                .compare()
+                .expect("hunks")
+            );
+            assert_ne!(result.hunks()[0], result.hunks()[1]);
+            let lines: HashSet<(LineKind, &str)> = result
+                .hunks()
+                .iter()
+                .flat_map(|hunk| hunk.lines().iter().map(|line| (line.kind(), line.inner())))
+                .collect();
+            assert_eq!(lines.len(), 2);
+            assert_eq!(result.to_string(), "2 hunks (+2/-2)");
+            assert_eq!(result.hunks().iter().collect::<HashSet<_>>().len(), 2);
+        }
+    }
+
+    mod byte_ranges {
+        use super::*;
+
+        #[test]
+        fn both_buffers() {
+            let old = "a\nb\nc\nd\n";
+            let new = "a\nB\nc\nd\ne\n";
+            let left = old.lines().collect::<Vec<_>>();
+            let right = new.lines().collect::<Vec<_>>();
+            let result = Comparison {
+                context_radius: 0,
+                ..Comparison::new(&left, &right)
+            }
+            .compare_infallible();
+
+            let ranges = result
+                .hunks()
+                .iter()
+                .map(|hunk| (hunk.byte_range(old), hunk.byte_range(new)))
+                .collect::<Vec<_>>();
+            assert_eq!(ranges, vec![(Some(2..3), Some(2..3)), (None, Some(8..9))]);
+            assert_eq!(&new[8..9], "e");
+        }
+    }
+
+    mod records {
+        use super::*;
+
+        const LEFT: &[&str] = &[
+            "Title", "", "First a", "common", "First b", "", "Second a", "common", "Second b",
+        ];
+        const RIGHT: &[&str] = &[
+            "Title", "", "Second a", "common", "Second b", "", "First a", "common", "First B",
+        ];
+
+        fn compare(records: Option<&(dyn Fn(&str) -> bool + Sync)>) -> CompareResult<'_> {
+            Comparison {
+                records,
+                ..Comparison::new(LEFT, RIGHT)
+            }
+            .compare_infallible()
+        }
+
+        #[test]
+        fn whole_records_are_matched() {
+            let result = compare(Some(&|line| line.trim().is_empty()));
+            assert!(result.verify(LEFT, RIGHT));
+
+            let changed = result.hunks()[0]
+                .lines()
+                .iter()
+                .filter(|line| line.kind() != LineKind::Unchanged)
+                .map(|line| format!("{}{}", line.kind().sign(), line.inner()))
+                .collect::<Vec<_>>();
+            // Moved paragraph is matched as a whole, not interleaved with the other one
+            assert_eq!(
+                changed,
+                vec![
+                    "-First a", "-common", "-First b", "-", "+", "+First a", "+common", "+First B"
+                ]
+            );
+        }
+
+        #[test]
+        fn valid_with_cleanup_and_without_separators() {
+            for semantic_cleanup in [false, true].iter().copied() {
+                let separators: [fn(&str) -> bool; 2] = [|_| true, |_| false];
+                for is_separator in separators.iter() {
+                    let result = Comparison {
+                        records: Some(is_separator),
+                        semantic_cleanup,
+                        ..Comparison::new(LEFT, RIGHT)
+                    }
+                    .compare_infallible();
+                    assert!(result.verify(LEFT, RIGHT));
+                    assert_eq!(result.reconstruct_right(LEFT).as_deref(), Some(RIGHT));
+                }
+            }
+            assert!(compare(None).verify(LEFT, RIGHT));
+        }
+    }
+
+    mod parallel {
+        use super::*;
+
+        fn texts() -> (Vec<String>, Vec<String>) {
+            let left = (0..2_000)
+                .map(|i| match i % 4 {
+                    0 => format!("item {}", i),
+                    1 => "{".to_string(),
+                    2 => format!("  value {}", i % 7),
+                    _ => "}".to_string(),
+                })
+                .collect::<Vec<_>>();
+            let mut right = left.clone();
+            right.retain(|line| !line.ends_with("99"));
+            for line in right.iter_mut().step_by(37) {
+                line.push('!');
+            }
+            right.insert(500, "item 600".to_string());
+            (left, right)
+        }
+
+        #[test]
+        fn same_as_single_threaded() {
+            let (left, right) = texts();
+            let left = left.iter().map(String::as_str).collect::<Vec<_>>();
+            let right = right.iter().map(String::as_str).collect::<Vec<_>>();
+            let single = Comparison::new(&left, &right).compare_infallible();
+
+            for threads in [0, 2, 3, 8, 5_000].iter().copied() {
+                let result = Comparison {
+                    threads,
+                    ..Comparison::new(&left, &right)
+                }
+                .compare_infallible();
+                assert!(result.verify(&left, &right), "threads: {}", threads);
+                assert_eq!(result, single, "threads: {}", threads);
+            }
+        }
+
+        #[test]
+        fn without_anchors() {
+            let left = ["a", "a", "b", "b"];
+            let right = ["b", "b", "a", "a", "c"];
+            let result = Comparison {
+                threads: 4,
+                ..Comparison::new(&left, &right)
+            }
+            .compare_infallible();
+            assert!(result.verify(&left, &right));
+        }
+    }
+
+    mod semantic_cleanup {
+        use super::*;
+
+        const LEFT: &[&str] = &[
+            "fn a() {", "  body", "}", "", "fn b() {", "  other", "}", "",
+        ];
+        const RIGHT: &[&str] = &[
+            "fn a() {", "  body", "}", "", "fn b() {", "  body", "}", "", "fn b() {", "  other",
+            "}", "",
+        ];
+
+        fn compare(semantic_cleanup: bool) -> Vec<String> {
+            let result = Comparison {
+                semantic_cleanup,
+                ..Comparison::new(LEFT, RIGHT)
+            }
+            .compare()
+            .expect("hunks");
+
+            result.hunks[0]
+                .lines
+                .iter()
+                .filter(|line| line.kind != LineKind::Unchanged)
+                .map(|line| format!("{}{}", line.kind.sign(), line.inner))
+                .collect()
+        }
+
+        #[test]
+        fn aligns_to_blank_lines() {
+            assert_eq!(compare(true), vec!["+fn b() {", "+  body", "+}", "+"]);
+        }
+
+        #[test]
+        fn same_changes_without_cleanup() {
+            assert_eq!(compare(false), vec!["+  body", "+}", "+", "+fn b() {"]);
+
+            let mut with = compare(true);
+            let mut without = compare(false);
+            with.sort();
+            without.sort();
+            assert_eq!(with, without);
+        }
+
+        #[test]
+        fn ties_take_earliest_position() {
+            let left = ["a", "b", "b", "c"];
+            for right in [["a", "b", "b", "b", "c"], ["a", "b", "B", "b", "c"]].iter() {
+                let result = Comparison {
+                    semantic_cleanup: true,
+                    ..Comparison::new(&left, right)
+                }
+                .compare()
+                .expect("hunks");
+                let changed = result.hunks[0]
+                    .lines
+                    .iter()
+                    .filter(|line| line.kind != LineKind::Unchanged)
+                    .map(|line| line.new_pos)
+                    .collect::<Vec<_>>();
+
+                let expected = if right[2] == "B" { Some(2) } else { Some(1) };
+                assert_eq!(changed, vec![expected]);
             }
         }
     }
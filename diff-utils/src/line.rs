@@ -1,15 +1,72 @@
+use std::collections::HashMap;
+use std::ops::Range;
+
 /// Contains one line represented by slice to the original/new file, its [`kind`](enum.LineKind.html)
 /// and positions in both files.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Line<'a> {
     pub(crate) kind: LineKind,
     pub(crate) inner: &'a str,
     pub(crate) old_pos: Option<usize>,
     pub(crate) new_pos: Option<usize>,
+    pub(crate) occurrence: Occurrence,
+}
+
+/// Tells which occurrence of the same content the line is, within the file it comes from.
+/// Handy when a line repeated many times changes its count.
+#[derive(Debug, PartialEq, Clone, Eq, Copy, Hash)]
+pub struct Occurrence {
+    pub(crate) index: usize,
+    pub(crate) count: usize,
+}
+
+impl Occurrence {
+    /// Which occurrence it is, counting from 1
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// How many times the content occurs in the file
+    pub fn count(&self) -> usize {
+        self.count
+    }
+    /// If the content occurs more than once in the file
+    pub fn is_repeated(&self) -> bool {
+        self.count > 1
+    }
+}
+
+impl Default for Occurrence {
+    fn default() -> Self {
+        Self { index: 1, count: 1 }
+    }
+}
+
+/// Computes occurrence of every line of the file.
+pub(crate) fn occurrences(text: &[&str]) -> Vec<Occurrence> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    let indices = text
+        .iter()
+        .map(|line| {
+            let count = counts.entry(line).or_default();
+            *count += 1;
+            *count
+        })
+        .collect::<Vec<_>>();
+
+    text.iter()
+        .zip(indices)
+        .map(|(line, index)| Occurrence {
+            index,
+            count: counts[line],
+        })
+        .collect()
 }
 
 /// Line kind specifies what happened to it.
-#[derive(Debug, PartialEq, Clone, PartialOrd, Ord, Eq, Copy)]
+///
+/// New kinds may be added in the future, so matches outside of this crate need a wildcard arm.
+#[derive(Debug, PartialEq, Clone, PartialOrd, Ord, Eq, Copy, Hash)]
+#[non_exhaustive]
 pub enum LineKind {
     /// It existed in original file but no more,
     Removed,
@@ -24,7 +81,8 @@ pub enum LineKind {
 }
 
 impl LineKind {
-    pub(crate) fn invert(self) -> Self {
+    /// Kind of the same line when files are swapped, e.g. `Removed` becomes `Inserted`.
+    pub fn invert(self) -> Self {
         match self {
             LineKind::Removed => LineKind::Inserted,
             LineKind::Inserted => LineKind::Removed,
@@ -34,7 +92,8 @@ impl LineKind {
         }
     }
 
-    pub(crate) fn sign(&self) -> &str {
+    /// Sign used in unified diffs: `+`, `-` or a space for unchanged lines.
+    pub fn sign(self) -> &'static str {
         match self {
             LineKind::ReplaceInserted | LineKind::Inserted => "+",
             LineKind::ReplaceRemoved | LineKind::Removed => "-",
@@ -42,18 +101,130 @@ impl LineKind {
         }
     }
 
-    pub(crate) fn is_replaced(self) -> bool {
+    /// If the line is a part of replacement, either removed or inserted one
+    pub fn is_replaced(self) -> bool {
         matches!(self, LineKind::ReplaceInserted | LineKind::ReplaceRemoved)
     }
+
+    /// If the line exists only in the new/right file
+    pub fn is_insertion(self) -> bool {
+        matches!(self, LineKind::Inserted | LineKind::ReplaceInserted)
+    }
+
+    /// If the line exists only in the old/left file
+    pub fn is_deletion(self) -> bool {
+        matches!(self, LineKind::Removed | LineKind::ReplaceRemoved)
+    }
+
+    /// If the line differs between files, i.e. it is not unchanged
+    pub fn is_change(self) -> bool {
+        self != LineKind::Unchanged
+    }
 }
 
 impl<'a> Line<'a> {
+    /// What happened to the line
+    pub fn kind(&self) -> LineKind {
+        self.kind
+    }
+    /// Content of the line
+    pub fn inner(&self) -> &'a str {
+        self.inner
+    }
+    /// Position in the old/left file, if the line exists there
+    pub fn old_pos(&self) -> Option<usize> {
+        self.old_pos
+    }
+    /// Position in the new/right file, if the line exists there
+    pub fn new_pos(&self) -> Option<usize> {
+        self.new_pos
+    }
+    /// Which occurrence of the same content the line is. Inserted lines are counted in the
+    /// new/right file, the rest in the old/left one.
+    pub fn occurrence(&self) -> Occurrence {
+        self.occurrence
+    }
+
+    /// Moves positions of the line by offsets of both files.
+    /// Maps positions in sorted files to positions in the original ones.
+    pub(crate) fn reorder(&mut self, left_order: &[usize], right_order: &[usize]) {
+        self.old_pos = self.old_pos.map(|pos| left_order[pos]);
+        self.new_pos = self.new_pos.map(|pos| right_order[pos]);
+    }
+
+    pub(crate) fn shift(&mut self, old: usize, new: usize) {
+        self.old_pos = self.old_pos.map(|pos| pos + old);
+        self.new_pos = self.new_pos.map(|pos| pos + new);
+    }
+
+    /// Looks up metadata of the line in slices parallel to the compared ones, e.g. byte offsets
+    /// or ids of original records. Returns `(left, right)`, each only if the line exists in that
+    /// file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let left = ["foo", "bar"];
+    /// let right = ["foo", "baz"];
+    /// let (left_ids, right_ids) = ([10, 11], [20, 21]);
+    ///
+    /// let result = Comparison::new(&left, &right).compare_infallible();
+    /// let ids = result.hunks()[0]
+    ///     .lines()
+    ///     .iter()
+    ///     .map(|line| line.metadata(&left_ids, &right_ids))
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(ids, vec![(Some(&10), Some(&20)), (Some(&11), None), (None, Some(&21))]);
+    /// ```
+    pub fn metadata<'m, M>(&self, left: &'m [M], right: &'m [M]) -> (Option<&'m M>, Option<&'m M>) {
+        let left = self
+            .old_pos
+            .filter(|_| !self.kind.is_insertion())
+            .and_then(|pos| left.get(pos));
+        let right = self
+            .new_pos
+            .filter(|_| !self.kind.is_deletion())
+            .and_then(|pos| right.get(pos));
+        (left, right)
+    }
+
+    /// Byte range of the line in the `original` buffer, if the compared lines were sliced from
+    /// it, e.g. with [`str::lines`]. Returns `None` if the content comes from a different buffer.
+    ///
+    /// Content of unchanged lines is taken from the left/old file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let original = "foo\nbar\n";
+    /// let left = original.lines().collect::<Vec<_>>();
+    ///
+    /// let result = Comparison::new(&left, &["foo"]).compare_infallible();
+    /// let removed = &result.hunks()[0].lines()[1];
+    /// assert_eq!(removed.byte_range(original), Some(4..7));
+    /// assert_eq!(removed.byte_range("bar"), None);
+    /// ```
+    pub fn byte_range(&self, original: &str) -> Option<Range<usize>> {
+        let start = (self.inner.as_ptr() as usize).checked_sub(original.as_ptr() as usize)?;
+        let end = start + self.inner.len();
+        if end <= original.len() {
+            Some(start..end)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn with_occurrence(self, occurrence: Occurrence) -> Self {
+        Self { occurrence, ..self }
+    }
+
     pub(crate) fn insert(pos: usize, inner: &'a str) -> Self {
         Line {
             kind: LineKind::Inserted,
             inner,
             old_pos: None,
             new_pos: Some(pos),
+            occurrence: Occurrence::default(),
         }
     }
 
@@ -63,6 +234,7 @@ impl<'a> Line<'a> {
             inner,
             old_pos: Some(pos),
             new_pos: None,
+            occurrence: Occurrence::default(),
         }
     }
 
@@ -72,6 +244,7 @@ impl<'a> Line<'a> {
             inner,
             old_pos,
             new_pos: Some(new_pos),
+            occurrence: Occurrence::default(),
         }
     }
 
@@ -81,6 +254,7 @@ impl<'a> Line<'a> {
             inner,
             old_pos: Some(old_pos),
             new_pos,
+            occurrence: Occurrence::default(),
         }
     }
 
@@ -90,6 +264,31 @@ impl<'a> Line<'a> {
             inner,
             old_pos: Some(old_pos),
             new_pos: Some(new_pos),
+            occurrence: Occurrence::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn kind_predicates() {
+        let kinds = [
+            LineKind::Removed,
+            LineKind::Inserted,
+            LineKind::ReplaceRemoved,
+            LineKind::ReplaceInserted,
+            LineKind::Unchanged,
+        ];
+        for kind in kinds.iter().copied() {
+            assert_eq!(kind.invert().invert(), kind);
+            assert_eq!(kind.is_insertion(), kind.invert().is_deletion());
+            assert_eq!(kind.is_change(), kind.is_insertion() || kind.is_deletion());
+            assert_eq!(kind.sign() == "+", kind.is_insertion());
         }
+        assert!(LineKind::ReplaceInserted.is_replaced());
+        assert!(!LineKind::Inserted.is_replaced());
     }
 }
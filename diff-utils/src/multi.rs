@@ -0,0 +1,61 @@
+/*
+
+Here is code for comparing many labeled sections at once
+
+*/
+use crate::{CompareResult, Comparison};
+
+/// Results of comparing many labeled sections, e.g. parts of a report, at once. Every section
+/// keeps its own hunks, with line numbers counted from the start of that section, and
+/// [`display`](struct.MultiCompareResult#method.display) renders all of them under their labels.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::MultiCompareResult;
+/// let result = MultiCompareResult::compare(&[
+///     ("header", &["foo"], &["foo"]),
+///     ("body", &["bar", "baz"], &["bar", "qux"]),
+/// ]);
+/// assert!(!result.is_empty());
+/// assert_eq!(result.changed().map(|(label, _)| label).collect::<Vec<_>>(), vec!["body"]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MultiCompareResult<'a> {
+    pub(crate) sections: Vec<(&'a str, CompareResult<'a>)>,
+}
+
+impl<'a> MultiCompareResult<'a> {
+    /// Compares `(label, left, right)` sections in order, with default
+    /// [`Comparison`](struct.Comparison.html) settings. Use
+    /// [`push`](#method.push) for custom ones.
+    pub fn compare(sections: &[(&'a str, &'a [&'a str], &'a [&'a str])]) -> Self {
+        let sections = sections
+            .iter()
+            .map(|(label, left, right)| (*label, Comparison::new(left, right).compare_infallible()))
+            .collect();
+        Self { sections }
+    }
+
+    /// Appends result of a section compared separately.
+    pub fn push(&mut self, label: &'a str, result: CompareResult<'a>) {
+        self.sections.push((label, result));
+    }
+
+    /// If none of the sections has any differences, it returns `true`.
+    pub fn is_empty(&self) -> bool {
+        self.sections.iter().all(|(_, result)| result.is_empty())
+    }
+
+    /// Slice of all the sections with their labels, in order.
+    pub fn sections(&self) -> &[(&'a str, CompareResult<'a>)] {
+        &self.sections
+    }
+
+    /// Only the sections which have differences.
+    pub fn changed(&self) -> impl Iterator<Item = (&'a str, &CompareResult<'a>)> {
+        self.sections
+            .iter()
+            .filter(|(_, result)| !result.is_empty())
+            .map(|(label, result)| (*label, result))
+    }
+}
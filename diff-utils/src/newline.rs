@@ -0,0 +1,187 @@
+use std::fmt;
+
+/// What separates lines of the text, for inputs which don't fit [`str::lines`] semantics, like
+/// NUL-separated output of `find -print0` or old-Mac files.
+///
+/// Like with [`str::lines`], a trailing separator doesn't start one more, empty line.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::{Comparison, Newline};
+///
+/// let left = Newline::Nul.split("foo\0bar\0");
+/// let right = Newline::Nul.split("foo\0baz\0");
+/// assert_eq!(left, ["foo", "bar"]);
+///
+/// let result = Comparison::new(&left, &right).compare_infallible();
+/// assert_eq!(result.hunks().len(), 1);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum Newline {
+    /// `\n` or `\r\n`, the same as [`str::lines`]. Default
+    #[default]
+    Lines,
+    /// Only `\n`, so `\r` is kept as a part of the line
+    Lf,
+    /// Only `\r\n`
+    CrLf,
+    /// Only `\r`, used by classic Mac OS
+    Cr,
+    /// NUL character, used by e.g. `find -print0` and `xargs -0`
+    Nul,
+    /// Any other character
+    Char(char),
+}
+
+impl Newline {
+    /// Splits the text into lines, without separators. With the `simd` feature, separators are
+    /// searched with `memchr`, which is much faster on large inputs.
+    pub fn split(self, text: &str) -> Vec<&str> {
+        #[cfg(feature = "simd")]
+        {
+            if let Some(lines) = self.split_simd(text) {
+                return lines;
+            }
+        }
+        self.split_std(text)
+    }
+
+    fn split_std(self, text: &str) -> Vec<&str> {
+        let mut lines: Vec<&str> = match self {
+            Newline::Lines => return text.lines().collect(),
+            Newline::Lf => text.split('\n').collect(),
+            Newline::CrLf => text.split("\r\n").collect(),
+            Newline::Cr => text.split('\r').collect(),
+            Newline::Nul => text.split('\0').collect(),
+            Newline::Char(separator) => text.split(separator).collect(),
+        };
+        if lines.last() == Some(&"") {
+            lines.pop();
+        }
+        lines
+    }
+
+    /// Same as [`split_std`](#method.split_std), unless the separator is not ASCII.
+    #[cfg(feature = "simd")]
+    fn split_simd(self, text: &str) -> Option<Vec<&str>> {
+        use memchr::{memchr_iter, memmem};
+
+        let bytes = text.as_bytes();
+        let mut lines = match self {
+            Newline::Lines => separated(text, memchr_iter(b'\n', bytes), 1, true),
+            Newline::Lf => separated(text, memchr_iter(b'\n', bytes), 1, false),
+            Newline::CrLf => separated(text, memmem::find_iter(bytes, b"\r\n"), 2, false),
+            Newline::Cr => separated(text, memchr_iter(b'\r', bytes), 1, false),
+            Newline::Nul => separated(text, memchr_iter(b'\0', bytes), 1, false),
+            Newline::Char(separator) if separator.is_ascii() => {
+                separated(text, memchr_iter(separator as u8, bytes), 1, false)
+            }
+            Newline::Char(_) => return None,
+        };
+        if lines.last() == Some(&"") {
+            lines.pop();
+        }
+        Some(lines)
+    }
+}
+
+/// Lines between separators of `len` bytes found at `ends`. ASCII separators never split
+/// a character. Lines ended with `\n` lose `\r` too with `crlf`, like in [`str::lines`].
+#[cfg(feature = "simd")]
+fn separated(text: &str, ends: impl Iterator<Item = usize>, len: usize, crlf: bool) -> Vec<&str> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for end in ends {
+        let line = &text[start..end];
+        lines.push(match crlf {
+            true => line.strip_suffix('\r').unwrap_or(line),
+            false => line,
+        });
+        start = end + len;
+    }
+    lines.push(&text[start..]);
+    lines
+}
+
+/// Writes the separator itself. [`Lines`](enum.Newline.html#variant.Lines) writes `\n`.
+impl fmt::Display for Newline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Newline::Lines | Newline::Lf => f.write_str("\n"),
+            Newline::CrLf => f.write_str("\r\n"),
+            Newline::Cr => f.write_str("\r"),
+            Newline::Nul => f.write_str("\0"),
+            Newline::Char(separator) => write!(f, "{}", separator),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split() {
+        let text = "a\r\nb\nc\rd\0e\r\n";
+        assert_eq!(Newline::Lines.split(text), ["a", "b", "c\rd\0e"]);
+        assert_eq!(Newline::Lf.split(text), ["a\r", "b", "c\rd\0e\r"]);
+        assert_eq!(Newline::CrLf.split(text), ["a", "b\nc\rd\0e"]);
+        assert_eq!(Newline::Cr.split(text), ["a", "\nb\nc", "d\0e", "\n"]);
+        assert_eq!(Newline::Nul.split(text), ["a\r\nb\nc\rd", "e\r\n"]);
+        assert_eq!(Newline::Char('b').split(text), ["a\r\n", "\nc\rd\0e\r\n"]);
+        assert!(Newline::Nul.split("").is_empty());
+        assert_eq!(Newline::Nul.split("\0"), [""]);
+    }
+
+    #[cfg(feature = "simd")]
+    #[test]
+    fn simd_same_as_std() {
+        let texts = [
+            "",
+            "\n",
+            "\r",
+            "\r\n",
+            "a\r",
+            "a\r\r\nb\n\n",
+            "\0a\r\nb\nc\rd\0e;",
+            "ą\nę\r\n",
+        ];
+        let newlines = [
+            Newline::Lines,
+            Newline::Lf,
+            Newline::CrLf,
+            Newline::Cr,
+            Newline::Nul,
+            Newline::Char(';'),
+            Newline::Char('ę'),
+        ];
+        for text in texts.iter() {
+            for newline in newlines.iter() {
+                assert_eq!(
+                    newline.split(text),
+                    newline.split_std(text),
+                    "{:?} {:?}",
+                    newline,
+                    text
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        for newline in [
+            Newline::Lf,
+            Newline::CrLf,
+            Newline::Cr,
+            Newline::Nul,
+            Newline::Char(';'),
+        ]
+        .iter()
+        {
+            let text = format!("a{0}{0}b", newline);
+            assert_eq!(newline.split(&text).join(&newline.to_string()), text);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! Contains [`Op`](enum.Op.html) and the recorder of the edit script.
+
+use crate::{Hunk, LineKind};
+use std::io;
+
+/// Single operation of the edit script, with the same meaning as methods of `diffs::Diff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    Equal {
+        old: usize,
+        new: usize,
+        len: usize,
+    },
+    Delete {
+        old: usize,
+        len: usize,
+        new: usize,
+    },
+    Insert {
+        old: usize,
+        new: usize,
+        len: usize,
+    },
+    Replace {
+        old: usize,
+        old_len: usize,
+        new: usize,
+        new_len: usize,
+    },
+}
+
+impl Op {
+    pub fn is_empty(&self) -> bool {
+        match *self {
+            Op::Equal { len, .. } | Op::Delete { len, .. } | Op::Insert { len, .. } => len == 0,
+            Op::Replace {
+                old_len, new_len, ..
+            } => old_len == 0 && new_len == 0,
+        }
+    }
+
+    /// Positions in both files at which the operation starts.
+    pub fn start(&self) -> (usize, usize) {
+        match *self {
+            Op::Equal { old, new, .. }
+            | Op::Delete { old, new, .. }
+            | Op::Insert { old, new, .. }
+            | Op::Replace { old, new, .. } => (old, new),
+        }
+    }
+
+    /// Number of lines the operation adds to a hunk.
+    pub fn lines(&self) -> usize {
+        match *self {
+            Op::Equal { len, .. } | Op::Delete { len, .. } | Op::Insert { len, .. } => len,
+            Op::Replace {
+                old_len, new_len, ..
+            } => old_len + new_len,
+        }
+    }
+}
+
+/// Records the edit script instead of processing it, so it can be post-processed and replayed.
+/// Recording fails once the script changes more than `max_changed` lines.
+#[derive(Debug, Default)]
+pub(crate) struct Recorder {
+    pub(crate) ops: Vec<Op>,
+    pub(crate) max_changed: Option<usize>,
+    changed: usize,
+}
+
+impl Recorder {
+    pub fn with_max_changed(max_changed: Option<usize>) -> Self {
+        Self {
+            max_changed,
+            ..Self::default()
+        }
+    }
+
+    pub fn result(self) -> Vec<Op> {
+        self.ops
+    }
+
+    fn count(&mut self, lines: usize) -> io::Result<()> {
+        self.changed += lines;
+        match self.max_changed {
+            Some(max) if self.changed > max => Err(io::Error::other(format!(
+                "result of at least {} lines exceeds the limit of {}",
+                self.changed, max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl diffs::Diff for Recorder {
+    type Error = io::Error;
+
+    fn equal(&mut self, old: usize, new: usize, len: usize) -> Result<(), Self::Error> {
+        match self.ops.last_mut() {
+            Some(Op::Equal { len: last, .. }) => *last += len,
+            _ => self.ops.push(Op::Equal { old, new, len }),
+        }
+        Ok(())
+    }
+
+    fn delete(&mut self, old: usize, len: usize, new: usize) -> Result<(), Self::Error> {
+        self.count(len)?;
+        match self.ops.last_mut() {
+            Some(Op::Delete { len: last, .. }) => *last += len,
+            _ => self.ops.push(Op::Delete { old, len, new }),
+        }
+        Ok(())
+    }
+
+    fn insert(&mut self, old: usize, new: usize, new_len: usize) -> Result<(), Self::Error> {
+        self.count(new_len)?;
+        match self.ops.last_mut() {
+            Some(Op::Insert { len: last, .. }) => *last += new_len,
+            _ => self.ops.push(Op::Insert {
+                old,
+                new,
+                len: new_len,
+            }),
+        }
+        Ok(())
+    }
+
+    fn replace(
+        &mut self,
+        old: usize,
+        old_len: usize,
+        new: usize,
+        new_len: usize,
+    ) -> Result<(), Self::Error> {
+        self.count(old_len + new_len)?;
+        self.ops.push(Op::Replace {
+            old,
+            old_len,
+            new,
+            new_len,
+        });
+        Ok(())
+    }
+}
+
+/// Edit script of the whole files described by `hunks`, with everything outside of them equal.
+pub(crate) fn from_hunks(hunks: &[Hunk], left_len: usize, right_len: usize) -> io::Result<Vec<Op>> {
+    use diffs::Diff;
+
+    let mut recorder = Recorder::default();
+    let (mut old, mut new) = (0, 0);
+    for line in hunks.iter().flat_map(|hunk| &hunk.lines) {
+        match (line.kind, line.old_pos, line.new_pos) {
+            (LineKind::Unchanged, Some(old_pos), Some(_)) => {
+                recorder.equal(old, new, old_pos + 1 - old)?;
+                new += old_pos + 1 - old;
+                old = old_pos + 1;
+            }
+            (LineKind::Removed, Some(old_pos), _)
+            | (LineKind::ReplaceRemoved, Some(old_pos), _) => {
+                recorder.equal(old, new, old_pos - old)?;
+                new += old_pos - old;
+                recorder.delete(old_pos, 1, new)?;
+                old = old_pos + 1;
+            }
+            (LineKind::Inserted, _, Some(new_pos))
+            | (LineKind::ReplaceInserted, _, Some(new_pos)) => {
+                recorder.equal(old, new, new_pos - new)?;
+                old += new_pos - new;
+                recorder.insert(old, new_pos, 1)?;
+                new = new_pos + 1;
+            }
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "line without position",
+                ))
+            }
+        }
+    }
+    recorder.equal(
+        old,
+        new,
+        left_len
+            .saturating_sub(old)
+            .min(right_len.saturating_sub(new)),
+    )?;
+    Ok(recorder.result())
+}
+
+/// Feeds recorded operations into another differ, finishing it afterwards.
+pub(crate) fn replay<D: diffs::Diff>(ops: &[Op], d: &mut D) -> Result<(), D::Error> {
+    feed(ops, d)?;
+    d.finish()
+}
+
+/// Feeds recorded operations into another differ, without finishing it.
+pub(crate) fn feed<D: diffs::Diff>(ops: &[Op], d: &mut D) -> Result<(), D::Error> {
+    for op in ops.iter().filter(|op| !op.is_empty()) {
+        match *op {
+            Op::Equal { old, new, len } => d.equal(old, new, len)?,
+            Op::Delete { old, len, new } => d.delete(old, len, new)?,
+            Op::Insert { old, new, len } => d.insert(old, new, len)?,
+            Op::Replace {
+                old,
+                old_len,
+                new,
+                new_len,
+            } => d.replace(old, old_len, new, new_len)?,
+        }
+    }
+    Ok(())
+}
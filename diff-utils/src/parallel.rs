@@ -0,0 +1,104 @@
+//! Chunked, multi-threaded mode of the comparison, for huge files.
+//!
+//! Lines unique to both sides, in the longest increasing order, are the same anchors the patience
+//! algorithm would match first. Some of them, evenly spread, split the files into regions which
+//! are compared in parallel threads. Recorded operations are then joined in order.
+
+use crate::ops::{self, Op, Recorder};
+use std::collections::HashMap;
+use std::io;
+use std::thread;
+
+pub(crate) fn diff(
+    recorder: &mut Recorder,
+    left: &[&str],
+    right: &[&str],
+    threads: usize,
+) -> io::Result<()> {
+    let anchors = anchors(left, right);
+    let splits = (1..threads)
+        .filter_map(|chunk| anchors.get(chunk * anchors.len() / threads))
+        .copied()
+        .fold(Vec::<(usize, usize)>::new(), |mut splits, anchor| {
+            if splits.last() != Some(&anchor) {
+                splits.push(anchor);
+            }
+            splits
+        });
+
+    let mut regions = Vec::with_capacity(splits.len() + 1);
+    let (mut old, mut new) = (0, 0);
+    for &(old_anchor, new_anchor) in &splits {
+        regions.push((old, old_anchor, new, new_anchor));
+        old = old_anchor + 1;
+        new = new_anchor + 1;
+    }
+    regions.push((old, left.len(), new, right.len()));
+
+    let results: Vec<io::Result<Vec<Op>>> = thread::scope(|scope| {
+        let handles = regions
+            .iter()
+            .map(|&(old, old_end, new, new_end)| {
+                scope.spawn(move || {
+                    let mut region = Recorder::default();
+                    diffs::patience::diff(&mut region, left, old, old_end, right, new, new_end)?;
+                    Ok(region.result())
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("Comparison thread panicked"))
+            .collect()
+    });
+
+    for (i, region) in results.into_iter().enumerate() {
+        if let Some(&(old, new)) = i.checked_sub(1).and_then(|prev| splits.get(prev)) {
+            diffs::Diff::equal(recorder, old, new, 1)?;
+        }
+        ops::feed(&region?, recorder)?;
+    }
+    Ok(())
+}
+
+/// Positions of lines which occur exactly once on both sides, forming the longest sequence
+/// increasing on both of them.
+fn anchors(left: &[&str], right: &[&str]) -> Vec<(usize, usize)> {
+    let mut counts: HashMap<&str, (usize, usize, usize)> = HashMap::new();
+    for (i, line) in left.iter().enumerate() {
+        let entry = counts.entry(line).or_insert((0, i, 0));
+        entry.0 += 1;
+    }
+    let mut unique = Vec::new();
+    for (j, line) in right.iter().enumerate() {
+        if let Some(entry) = counts.get_mut(line) {
+            entry.2 += 1;
+            if entry.0 == 1 && entry.2 == 1 {
+                unique.push((entry.1, j));
+            }
+        }
+    }
+    // Lines which turned out to be repeated on the right side.
+    unique.retain(|(i, _)| counts[left[*i]].2 == 1);
+    unique.sort_unstable();
+
+    // Patience sorting: tops of piles and the predecessor of every anchor.
+    let mut piles: Vec<usize> = Vec::new();
+    let mut previous = vec![None; unique.len()];
+    for (k, &(_, j)) in unique.iter().enumerate() {
+        let pile = piles.partition_point(|&top| unique[top].1 < j);
+        previous[k] = pile.checked_sub(1).map(|prev| piles[prev]);
+        match piles.get_mut(pile) {
+            Some(top) => *top = k,
+            None => piles.push(k),
+        }
+    }
+    let mut sequence = Vec::with_capacity(piles.len());
+    let mut k = piles.last().copied();
+    while let Some(current) = k {
+        sequence.push(unique[current]);
+        k = previous[current];
+    }
+    sequence.reverse();
+    sequence
+}
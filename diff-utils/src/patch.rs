@@ -3,13 +3,19 @@
 Here is code for creating nice patch
 
 */
-use crate::{CompareResult, Hunk};
-use chrono::format::{DelayedFormat, StrftimeItems};
+use crate::{CompareResult, Hunk, LineKind, RenderOptions};
 use std::borrow::Cow;
 use std::fmt;
 
-/// Options for creating patch files
+/// Options for creating patch files. New options may be added in any release, so outside of this
+/// crate it is built from the default with builder methods named after the fields:
+/// ```rust
+/// use diff_utils::PatchOptions;
+/// let options = PatchOptions::default().offset(0).crlf(true);
+/// assert!(options.crlf);
+/// ```
 #[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
 pub struct PatchOptions {
     /// Sometimes user want's to compare only subslice of a full str. This argument gives
     /// possibility to "move" whole patch to proper offset.
@@ -18,7 +24,6 @@ pub struct PatchOptions {
     ///
     /// ```rust
     /// use diff_utils::{Comparison, PatchOptions};
-    /// use chrono::{DateTime, Utc, TimeZone};
     ///
     /// let file_a = (0..1000).map(|i| if i%2 == 0 { "foo\n" } else { "bar\n" }).collect::<Vec<&str>>();
     /// let file_b = (0..1000).map(|i| if i%5 == 0 { "foo\n" } else { "bar\n" }).collect::<Vec<&str>>();
@@ -26,10 +31,8 @@ pub struct PatchOptions {
     /// let subslice_a = file_a.into_iter().skip(123).take(10).collect::<Vec<&str>>();
     /// let subslice_b = file_b.into_iter().skip(123).take(10).collect::<Vec<&str>>();
     ///
-    /// let left_datetime: DateTime<Utc> = Utc.ymd(2015, 2, 18).and_hms(23, 16, 9);
-    /// let left_dt = left_datetime.format("%F %T %z");
-    /// let right_datetime: DateTime<Utc> = Utc.ymd(2020, 4, 20).and_hms(4, 20, 4);
-    /// let right_dt = right_datetime.format("%F %T %z");
+    /// let left_dt = "2015-02-18 23:16:09 +0000";
+    /// let right_dt = "2020-04-20 04:20:04 +0000";
     ///
     /// let result = Comparison::new(&subslice_a, &subslice_b).compare().unwrap();
     /// println!("{}", result.patch(
@@ -37,7 +40,7 @@ pub struct PatchOptions {
     ///         &left_dt,
     ///         "right.txt".into(),
     ///         &right_dt,
-    ///         PatchOptions { offset: 123, ..Default::default() }));
+    ///         PatchOptions::default().offset(123)));
     /// ```
     ///
     /// Thanks to the `offset` the output will be:
@@ -62,21 +65,87 @@ pub struct PatchOptions {
     ///
     /// Default value: 1 - because in IT we count offsets from 0 but in files we count lines from 1
     pub offset: usize,
+    /// Terminates hunk lines with `\r\n`, so patches of files with Windows line endings apply
+    /// cleanly. Headers are always terminated with `\n`, like GNU diff does. The flag applies to
+    /// every line, so for files mixing line endings split them with
+    /// [`Newline::Lf`](enum.Newline.html#variant.Lf) instead, which keeps `\r` as a part of
+    /// each line, and leave the flag off.
+    ///
+    /// ```rust
+    /// use diff_utils::{Comparison, Newline, PatchOptions};
+    /// let left = Newline::Lf.split("a\r\nb\nc\r\n");
+    /// let right = Newline::Lf.split("a\r\nB\nc\r\n");
+    /// let result = Comparison::new(&left, &right).compare_infallible();
+    /// let patch = result.patch("left".into(), &"", "right".into(), &"", PatchOptions::default());
+    /// assert!(patch.to_string().ends_with("@@\n a\r\n-b\n+B\n c\r\n"));
+    /// ```
+    ///
+    /// Default value: false
+    pub crlf: bool,
+    /// The left/old file doesn't end with a new line, so its last line is followed by
+    /// `\ No newline at end of file`. Markers are written only by
+    /// [`CompareResult::patch`](struct.CompareResult.html#method.patch), which knows where files
+    /// end. An unchanged last line is marked only if both files miss the new line.
+    ///
+    /// Default value: false
+    pub left_no_newline: bool,
+    /// The same as [`left_no_newline`](#structfield.left_no_newline), but for the right/new file.
+    ///
+    /// Default value: false
+    pub right_no_newline: bool,
+}
+
+impl PatchOptions {
+    /// Sets [`offset`](#structfield.offset)
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Takes options shared with display, i.e. the [`offset`](#structfield.offset)
+    pub fn render(mut self, render: &RenderOptions) -> Self {
+        self.offset = render.offset;
+        self
+    }
+
+    /// Sets [`crlf`](#structfield.crlf)
+    pub fn crlf(mut self, crlf: bool) -> Self {
+        self.crlf = crlf;
+        self
+    }
+
+    /// Sets [`left_no_newline`](#structfield.left_no_newline)
+    pub fn left_no_newline(mut self, no_newline: bool) -> Self {
+        self.left_no_newline = no_newline;
+        self
+    }
+
+    /// Sets [`right_no_newline`](#structfield.right_no_newline)
+    pub fn right_no_newline(mut self, no_newline: bool) -> Self {
+        self.right_no_newline = no_newline;
+        self
+    }
 }
 
 impl Default for PatchOptions {
     fn default() -> Self {
-        Self { offset: 1 }
+        Self {
+            offset: 1,
+            crlf: false,
+            left_no_newline: false,
+            right_no_newline: false,
+        }
     }
 }
 
 impl<'a> Hunk<'a> {
     /// Returns a structure which implements [`Display`](std::fmt::Display) for generating patch
     /// in [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html).
-    pub fn patch(&self, options: PatchOptions) -> HunkPatch {
+    pub fn patch(&self, options: PatchOptions) -> HunkPatch<'_> {
         HunkPatch {
             hunk: self,
             options,
+            last_lines: self.last_lines,
         }
     }
 }
@@ -88,22 +157,31 @@ impl<'a> Hunk<'a> {
 pub struct HunkPatch<'a> {
     hunk: &'a Hunk<'a>,
     options: PatchOptions,
+    /// Positions of the last lines of files without the new line at the end
+    last_lines: (Option<usize>, Option<usize>),
 }
 
 impl<'a> fmt::Display for HunkPatch<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let header = format!(
-            "@@ -{},{} +{},{} @@",
-            self.hunk.old_start + self.options.offset,
-            self.hunk.removed,
-            self.hunk.new_start + self.options.offset,
-            self.hunk.inserted,
-        );
-        writeln!(f, "{}", header)?;
+        writeln!(f, "{}", self.hunk.header(self.options.offset))?;
 
+        let eol = if self.options.crlf { "\r\n" } else { "\n" };
+        let (left_last, right_last) = self.last_lines;
         for line in self.hunk.lines.iter() {
             let sign = line.kind.sign();
-            writeln!(f, "{}{}", sign, line.inner)?;
+            let is_last = |last: Option<usize>, pos: Option<usize>| last.is_some() && last == pos;
+            let no_newline = match line.kind {
+                LineKind::Unchanged => {
+                    is_last(left_last, line.old_pos) && is_last(right_last, line.new_pos)
+                }
+                kind if kind.is_deletion() => is_last(left_last, line.old_pos),
+                _ => is_last(right_last, line.new_pos),
+            };
+            if no_newline {
+                writeln!(f, "{}{}\n\\ No newline at end of file", sign, line.inner)?;
+            } else {
+                write!(f, "{}{}{}", sign, line.inner, eol)?;
+            }
         }
         Ok(())
     }
@@ -112,12 +190,19 @@ impl<'a> fmt::Display for HunkPatch<'a> {
 impl<'a> CompareResult<'a> {
     /// Returns a structure which implements [`Display`](std::fmt::Display) for generating patch
     /// in [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html).
+    ///
+    /// Timestamps are written after the file names as they are, so plain strings work fine.
+    /// With `patch-chrono` feature [`patch_timestamp`](fn.patch_timestamp.html) formats
+    /// `chrono` dates the same way as GNU diff does.
+    ///
+    /// Context of hunks is the one of the comparison, use
+    /// [`with_context`](struct.CompareResult.html#method.with_context) to change it for the patch.
     pub fn patch(
         &'a self,
         left_name: Cow<'a, str>,
-        left_dt: &'a DelayedFormat<StrftimeItems<'a>>,
+        left_dt: &'a dyn fmt::Display,
         right_name: Cow<'a, str>,
-        right_dt: &'a DelayedFormat<StrftimeItems<'a>>,
+        right_dt: &'a dyn fmt::Display,
         options: PatchOptions,
     ) -> CompareResultPatch<'a> {
         CompareResultPatch {
@@ -129,28 +214,122 @@ impl<'a> CompareResult<'a> {
             options,
         }
     }
+
+    /// Patch in [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html)
+    /// with files labeled `expected` and `actual`, without timestamps and with default
+    /// [`PatchOptions`](struct.PatchOptions.html). Empty if there are no differences.
+    /// See [`patch_string`](#method.patch_string) for other labels and options.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::Comparison;
+    /// let result = Comparison::new(&["foo", "bar"], &["foo", "baz"]).compare_infallible();
+    /// assert_eq!(
+    ///     result.to_patch_string(),
+    ///     "--- expected\n+++ actual\n@@ -1,2 +1,2 @@\n foo\n-bar\n+baz\n"
+    /// );
+    /// assert_eq!(Comparison::new(&["foo"], &["foo"]).compare_infallible().to_patch_string(), "");
+    /// ```
+    pub fn to_patch_string(&self) -> String {
+        self.patch_string(&RenderOptions::default(), PatchOptions::default())
+    }
+
+    /// Patch without timestamps, with file labels and the offset taken from `render`. Empty if
+    /// there are no differences.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::{Comparison, PatchOptions, RenderOptions};
+    /// let render = RenderOptions::default().offset(100).labels("old.txt", "new.txt");
+    /// let result = Comparison::new(&["foo"], &["bar"]).compare_infallible();
+    ///
+    /// let patch = result.patch_string(&render, PatchOptions::default());
+    /// assert_eq!(patch, "--- old.txt\n+++ new.txt\n@@ -100 +100 @@\n-foo\n+bar\n");
+    /// ```
+    pub fn patch_string(&self, render: &RenderOptions, options: PatchOptions) -> String {
+        if self.is_empty() {
+            return String::new();
+        }
+        let options = options.render(render);
+        let last_lines = self.last_lines(options);
+        let mut patch = format!("--- {}\n+++ {}\n", render.left_label, render.right_label);
+        for hunk in &self.hunks {
+            let hunk = HunkPatch {
+                hunk,
+                options,
+                last_lines,
+            };
+            patch += &hunk.to_string();
+        }
+        patch
+    }
+
+    /// Positions of the last lines of files which miss the new line at the end.
+    fn last_lines(&self, options: PatchOptions) -> (Option<usize>, Option<usize>) {
+        let last = |no_newline: bool, len: usize| len.checked_sub(1).filter(|_| no_newline);
+        (
+            last(options.left_no_newline, self.left_len),
+            last(options.right_no_newline, self.right_len),
+        )
+    }
 }
 
 /// Structure which implements [`Display`](std::fmt::Display) for generating patch in
 /// in [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html).
 /// It is a wrapper to the [`CompareResult`](struct.CompareResult.html).
-#[derive(Debug)]
 pub struct CompareResultPatch<'a> {
     result: &'a CompareResult<'a>,
     left_name: Cow<'a, str>,
     right_name: Cow<'a, str>,
-    left_dt: &'a DelayedFormat<StrftimeItems<'a>>,
-    right_dt: &'a DelayedFormat<StrftimeItems<'a>>,
+    left_dt: &'a dyn fmt::Display,
+    right_dt: &'a dyn fmt::Display,
     options: PatchOptions,
 }
 
+impl<'a> fmt::Debug for CompareResultPatch<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CompareResultPatch")
+            .field("result", &self.result)
+            .field("left_name", &self.left_name)
+            .field("right_name", &self.right_name)
+            .field("left_dt", &self.left_dt.to_string())
+            .field("right_dt", &self.right_dt.to_string())
+            .field("options", &self.options)
+            .finish()
+    }
+}
+
 impl<'a> fmt::Display for CompareResultPatch<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         writeln!(f, "--- {}\t{}", self.left_name, self.left_dt)?;
         writeln!(f, "+++ {}\t{}", self.right_name, self.right_dt)?;
+        let last_lines = self.result.last_lines(self.options);
         for hunk in &self.result.hunks {
-            hunk.patch(self.options).fmt(f)?;
+            HunkPatch {
+                hunk,
+                options: self.options,
+                last_lines,
+            }
+            .fmt(f)?;
         }
         Ok(())
     }
 }
+
+/// Formats the date like GNU diff does in file headers, e.g. `2020-06-27 18:10:03.000000000 +0200`.
+///
+/// # Example
+/// ```rust
+/// use chrono::{FixedOffset, TimeZone};
+/// let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+/// let dt = offset.with_ymd_and_hms(2020, 6, 27, 18, 10, 3).unwrap();
+/// assert_eq!(diff_utils::patch_timestamp(&dt).to_string(), "2020-06-27 18:10:03.000000000 +0200");
+/// ```
+#[cfg(feature = "patch-chrono")]
+pub fn patch_timestamp<Tz>(datetime: &chrono::DateTime<Tz>) -> impl fmt::Display
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: fmt::Display,
+{
+    datetime.format("%F %T%.9f %z")
+}
@@ -0,0 +1,372 @@
+/*
+
+Here is code for patches spanning multiple files
+
+*/
+use crate::apply::{self, Direction};
+use crate::context::Context;
+use crate::{CompareResult, Hunk, Line, PatchOptions};
+use std::borrow::Cow;
+use std::fmt;
+use std::io;
+
+/// Patch of a single file pair, a part of the [`PatchSet`](struct.PatchSet.html).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilePatch<'a> {
+    /// Name of the left/old file, written after `---`
+    pub left_name: Cow<'a, str>,
+    /// Timestamp (or any other label) of the left/old file. Empty if there is none
+    pub left_timestamp: Cow<'a, str>,
+    /// Name of the right/new file, written after `+++`
+    pub right_name: Cow<'a, str>,
+    /// Timestamp (or any other label) of the right/new file. Empty if there is none
+    pub right_timestamp: Cow<'a, str>,
+    /// Differences between both files
+    pub hunks: Vec<Hunk<'a>>,
+}
+
+impl<'a> FilePatch<'a> {
+    /// Creates file patch without timestamps, from the result of the comparison.
+    pub fn new(
+        left_name: impl Into<Cow<'a, str>>,
+        right_name: impl Into<Cow<'a, str>>,
+        result: CompareResult<'a>,
+    ) -> Self {
+        Self {
+            left_name: left_name.into(),
+            left_timestamp: Cow::Borrowed(""),
+            right_name: right_name.into(),
+            right_timestamp: Cow::Borrowed(""),
+            hunks: result.hunks,
+        }
+    }
+
+    /// Rebuilds the right/new file by applying hunks on the `left` one, e.g. to synthesize a new
+    /// expected file from a stored patch. Returns `None` if hunks don't match the `left` file.
+    ///
+    /// # Example
+    /// ```rust
+    /// use diff_utils::PatchSet;
+    /// let set = PatchSet::parse("--- a\n+++ b\n@@ -1,2 +1,2 @@\n foo\n-bar\n+baz\n")?;
+    /// let file = &set.files[0];
+    /// assert_eq!(file.reconstruct_right(&["foo", "bar"]), Some(vec!["foo", "baz"]));
+    /// assert_eq!(file.reconstruct_left(&["foo", "baz"]), Some(vec!["foo", "bar"]));
+    /// assert_eq!(file.reconstruct_right(&["foo", "qux"]), None);
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn reconstruct_right<'t>(&'t self, left: &[&'t str]) -> Option<Vec<&'t str>> {
+        apply::apply(&self.hunks, left, Direction::Forward)
+    }
+
+    /// Rebuilds the left/old file by reverting hunks on the `right` one.
+    /// Returns `None` if hunks don't match the `right` file.
+    pub fn reconstruct_left<'t>(&'t self, right: &[&'t str]) -> Option<Vec<&'t str>> {
+        apply::apply(&self.hunks, right, Direction::Backward)
+    }
+}
+
+/// Patch series for multiple files, rendered as one concatenated
+/// [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html)
+/// and parsed back.
+///
+/// # Example
+/// ```rust
+/// use diff_utils::{Comparison, FilePatch, PatchOptions, PatchSet};
+///
+/// let mut set = PatchSet::default();
+/// set.files.push(FilePatch::new("a.txt", "a.txt", Comparison::new(&["foo"], &["bar"]).compare()?));
+/// set.files.push(FilePatch::new("b.txt", "b.txt", Comparison::new(&["baz"], &[]).compare()?));
+///
+/// let patch = set.patch(PatchOptions::default()).to_string();
+/// assert_eq!(patch, "--- a.txt\n+++ a.txt\n@@ -1 +1 @@\n-foo\n+bar\n--- b.txt\n+++ b.txt\n@@ -1 +0,0 @@\n-baz\n");
+///
+/// let parsed = PatchSet::parse(&patch)?;
+/// assert_eq!(parsed.files.len(), 2);
+/// assert_eq!(parsed.patch(PatchOptions::default()).to_string(), patch);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PatchSet<'a> {
+    /// Patches of every file pair, in order they are rendered
+    pub files: Vec<FilePatch<'a>>,
+}
+
+impl<'a> PatchSet<'a> {
+    /// Parses a unified diff with any number of files. Lines of hunks borrow from the `text`.
+    /// Anything outside file headers and hunks, like `diff --git` lines, is skipped.
+    ///
+    /// Line numbers are expected to be counted from 1, which is the case for GNU diff and
+    /// for patches rendered with the default [`PatchOptions`](struct.PatchOptions.html).
+    ///
+    /// Parsed hunks contain only removed, inserted and unchanged lines, because unified format
+    /// doesn't distinguish replacements.
+    /// `\ No newline at end of file` markers are kept by hunks and rendered again.
+    ///
+    /// # Errors
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) if a header or a hunk is malformed.
+    pub fn parse(text: &'a str) -> io::Result<Self> {
+        let mut lines = text.lines().enumerate().peekable();
+        let mut files = Vec::new();
+
+        while let Some((no, line)) = lines.next() {
+            let left = match line.strip_prefix("--- ") {
+                Some(left) => left,
+                None => continue,
+            };
+            let right = match lines.next() {
+                Some((_, line)) if line.starts_with("+++ ") => &line[4..],
+                _ => return Err(invalid(no + 1, "expected `+++` after `---`")),
+            };
+            let (left_name, left_timestamp) = split_file_header(left);
+            let (right_name, right_timestamp) = split_file_header(right);
+
+            let mut hunks = Vec::new();
+            while let Some((no, header)) = lines.next_if(|(_, line)| line.starts_with("@@ ")) {
+                let (old_start, mut old_left, new_start, mut new_left) =
+                    parse_header(header).ok_or_else(|| invalid(no + 1, "malformed hunk header"))?;
+                // Counts come from the untrusted header, so they don't size the allocation.
+                let mut context = Context::new(old_start, new_start);
+
+                while old_left > 0 || new_left > 0 {
+                    let (no, line) = lines
+                        .next()
+                        .ok_or_else(|| invalid(no + 1, "unexpected end of hunk"))?;
+                    let old_pos = context.old_start + context.removed;
+                    let new_pos = context.new_start + context.inserted;
+                    let inner = line.get(1..).unwrap_or_default();
+                    match line.as_bytes().first() {
+                        Some(b'-') if old_left > 0 => {
+                            old_left -= 1;
+                            context.push(Line::remove(old_pos, inner));
+                        }
+                        Some(b'+') if new_left > 0 => {
+                            new_left -= 1;
+                            context.push(Line::insert(new_pos, inner));
+                        }
+                        // Some tools strip trailing whitespace, so empty line is empty context.
+                        Some(b' ') | None if old_left > 0 && new_left > 0 => {
+                            old_left -= 1;
+                            new_left -= 1;
+                            context.push(Line::unchanged(old_pos, new_pos, inner));
+                        }
+                        Some(b'\\') => mark_no_newline(&mut context),
+                        _ => return Err(invalid(no + 1, "line doesn't match hunk header")),
+                    }
+                }
+                if lines.next_if(|(_, line)| line.starts_with('\\')).is_some() {
+                    mark_no_newline(&mut context);
+                }
+                hunks.push(context.create_hunk());
+            }
+
+            files.push(FilePatch {
+                left_name: Cow::Borrowed(left_name),
+                left_timestamp: Cow::Borrowed(left_timestamp),
+                right_name: Cow::Borrowed(right_name),
+                right_timestamp: Cow::Borrowed(right_timestamp),
+                hunks,
+            });
+        }
+        Ok(Self { files })
+    }
+
+    /// Returns a structure which implements [`Display`](std::fmt::Display) for generating patch
+    /// in [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html).
+    pub fn patch(&'a self, options: PatchOptions) -> PatchSetPatch<'a> {
+        PatchSetPatch { set: self, options }
+    }
+}
+
+/// Structure which implements [`Display`](std::fmt::Display) for generating patch in
+/// in [Unified Patch Format](https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html).
+/// It is a wrapper to the [`PatchSet`](struct.PatchSet.html).
+#[derive(Debug)]
+pub struct PatchSetPatch<'a> {
+    set: &'a PatchSet<'a>,
+    options: PatchOptions,
+}
+
+impl<'a> fmt::Display for PatchSetPatch<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for file in &self.set.files {
+            write_file_header(f, "---", &file.left_name, &file.left_timestamp)?;
+            write_file_header(f, "+++", &file.right_name, &file.right_timestamp)?;
+            for hunk in &file.hunks {
+                hunk.patch(self.options).fmt(f)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// `\ No newline at end of file` follows the last parsed line, so it is the last one of its file.
+fn mark_no_newline(context: &mut Context) {
+    let (left_last, right_last) = &mut context.last_lines;
+    if let Some(line) = context.data.last() {
+        if !line.kind.is_insertion() {
+            *left_last = line.old_pos;
+        }
+        if !line.kind.is_deletion() {
+            *right_last = line.new_pos;
+        }
+    }
+}
+
+fn write_file_header(
+    f: &mut fmt::Formatter,
+    prefix: &str,
+    name: &str,
+    timestamp: &str,
+) -> fmt::Result {
+    if timestamp.is_empty() {
+        writeln!(f, "{} {}", prefix, name)
+    } else {
+        writeln!(f, "{} {}\t{}", prefix, name, timestamp)
+    }
+}
+
+fn split_file_header(header: &str) -> (&str, &str) {
+    match header.find('\t') {
+        Some(tab) => (&header[..tab], &header[tab + 1..]),
+        None => (header, ""),
+    }
+}
+
+/// Parses `@@ -a,b +c,d @@` into 0-based starts and counts of both sides.
+fn parse_header(header: &str) -> Option<(usize, usize, usize, usize)> {
+    let mut parts = header.split(' ');
+    if parts.next()? != "@@" {
+        return None;
+    }
+    let (old_start, old_count) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let (new_start, new_count) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    if parts.next()? != "@@" {
+        return None;
+    }
+    Some((old_start, old_count, new_start, new_count))
+}
+
+/// Reverse of the hunk header range: empty ranges point to the line preceding them.
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let (start, count) = match range.find(',') {
+        Some(comma) => (
+            range[..comma].parse().ok()?,
+            range[comma + 1..].parse().ok()?,
+        ),
+        None => (range.parse().ok()?, 1),
+    };
+    match count {
+        0 => Some((start, 0)),
+        _ => Some((start.checked_sub(1)?, count)),
+    }
+}
+
+fn invalid(line: usize, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid patch at line {}: {}", line, msg),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Comparison;
+
+    const LEFT: &[&str] = &["a", "b", "c", "d", "e", "f", "g", "h", "i"];
+    const RIGHT: &[&str] = &["x", "a", "c", "D", "e", "f", "g", "h", "i", "y"];
+
+    #[test]
+    fn round_trip() {
+        for context_radius in 0..4 {
+            let compare = |left, right| {
+                Comparison {
+                    context_radius,
+                    ..Comparison::new(left, right)
+                }
+                .compare()
+                .unwrap()
+            };
+            let mut set = PatchSet::default();
+            set.files.push(FilePatch {
+                left_timestamp: "2020-06-27 18:10:03 +0200".into(),
+                ..FilePatch::new("left", "right", compare(LEFT, RIGHT))
+            });
+            set.files
+                .push(FilePatch::new("empty", "full", compare(&[], RIGHT)));
+            set.files
+                .push(FilePatch::new("same", "same", compare(LEFT, LEFT)));
+
+            let patch = set.patch(PatchOptions::default()).to_string();
+            let parsed = PatchSet::parse(&patch).unwrap();
+            assert_eq!(parsed.patch(PatchOptions::default()).to_string(), patch);
+            assert_eq!(parsed.files.len(), 3);
+            assert!(parsed.files[2].hunks.is_empty());
+            assert_eq!(
+                parsed.files[0].reconstruct_right(LEFT).as_deref(),
+                Some(RIGHT)
+            );
+            assert_eq!(
+                parsed.files[0].reconstruct_left(RIGHT).as_deref(),
+                Some(LEFT)
+            );
+            assert_eq!(
+                parsed.files[1].reconstruct_right(&[]).as_deref(),
+                Some(RIGHT)
+            );
+        }
+    }
+
+    #[test]
+    fn gnu_diff_output() {
+        let patch = "diff -u a b\n\
+                     --- a\t2020-06-27 18:10:03 +0200\n\
+                     +++ b\t2020-06-27 18:10:04 +0200\n\
+                     @@ -1,2 +1,2 @@\n\
+                     -foo\n\
+                     +bar\n\
+                     \x20baz\n\
+                     \\ No newline at end of file\n";
+        let parsed = PatchSet::parse(patch).unwrap();
+        let file = &parsed.files[0];
+        assert_eq!(file.right_timestamp, "2020-06-27 18:10:04 +0200");
+        assert_eq!(file.hunks[0].old_start(), 0);
+        assert_eq!(
+            file.hunks[0]
+                .lines()
+                .iter()
+                .map(|line| (line.old_pos(), line.new_pos(), line.inner()))
+                .collect::<Vec<_>>(),
+            vec![
+                (Some(0), None, "foo"),
+                (None, Some(0), "bar"),
+                (Some(1), Some(1), "baz")
+            ]
+        );
+        assert_eq!(
+            parsed.patch(PatchOptions::default()).to_string(),
+            patch.trim_start_matches("diff -u a b\n")
+        );
+
+        let patch = "--- a\n\
+                     +++ b\n\
+                     @@ -1 +1 @@\n\
+                     -foo\n\
+                     \\ No newline at end of file\n\
+                     +bar\n";
+        let parsed = PatchSet::parse(patch).unwrap();
+        assert_eq!(parsed.files[0].hunks[0].last_lines, (Some(0), None));
+        assert_eq!(parsed.patch(PatchOptions::default()).to_string(), patch);
+    }
+
+    #[test]
+    fn malformed() {
+        assert!(PatchSet::parse("--- a\nfoo\n").is_err());
+        assert!(PatchSet::parse("--- a\n+++ b\n@@ -1,2 +1 @@\n-foo\n").is_err());
+        assert!(PatchSet::parse("--- a\n+++ b\n@@ -1 +1 @@\n+foo\n+bar\n").is_err());
+        assert!(PatchSet::parse("--- a\n+++ b\n@@ -x +1 @@\n").is_err());
+        assert!(PatchSet::parse("--- a\n+++ b\n@@ -1,1 +1,1000000000000 @@\n-foo\n").is_err());
+        let huge = format!("--- a\n+++ b\n@@ -1,1 +1,{} @@\n-foo\n", usize::MAX);
+        assert!(PatchSet::parse(&huge).is_err());
+    }
+}
@@ -0,0 +1,91 @@
+//! Representative corpora for benchmarks in `benches/` and for the `perf` integration test,
+//! which checks that comparisons finish within time budgets.
+
+use crate::Comparison;
+use std::time::{Duration, Instant};
+
+/// Inputs of a single benchmark, with a time budget for the perf regression test.
+#[derive(Debug, Clone)]
+pub struct Corpus {
+    /// Short name, reported by benchmarks and in failed budget assertions
+    pub name: &'static str,
+    /// Left/old file
+    pub left: Vec<String>,
+    /// Right/new file
+    pub right: Vec<String>,
+    /// How long the comparison may take in an optimized build, before scaling with
+    /// [`budget_scale`](fn.budget_scale.html)
+    pub budget: Duration,
+}
+
+impl Corpus {
+    /// Runs the comparison once and returns how long it took, with the number of hunks.
+    pub fn run(&self) -> (Duration, usize) {
+        let left = self.left.iter().map(String::as_str).collect::<Vec<_>>();
+        let right = self.right.iter().map(String::as_str).collect::<Vec<_>>();
+        let start = Instant::now();
+        let hunks = Comparison::new(&left, &right)
+            .compare_infallible()
+            .hunks()
+            .len();
+        (start.elapsed(), hunks)
+    }
+}
+
+/// Large equal files, scattered changes, pathological unrelated files and files full of
+/// repeated lines.
+pub fn corpora() -> Vec<Corpus> {
+    let code = |i: usize| match i % 5 {
+        0 => format!("fn item_{}() {{", i),
+        1 => format!("    let value = {};", i * 7),
+        2 => "    process(value);".to_string(),
+        3 => "}".to_string(),
+        _ => String::new(),
+    };
+    let file = (0..100_000).map(code).collect::<Vec<_>>();
+
+    let mut scattered = file.clone();
+    for line in scattered.iter_mut().step_by(97) {
+        line.push_str(" // changed");
+    }
+
+    vec![
+        Corpus {
+            name: "equal",
+            left: file.clone(),
+            right: file.clone(),
+            budget: Duration::from_millis(500),
+        },
+        Corpus {
+            name: "scattered",
+            left: file.clone(),
+            right: scattered,
+            budget: Duration::from_secs(1),
+        },
+        Corpus {
+            name: "unrelated",
+            left: (0..2_000).map(|i| format!("left {}", i)).collect(),
+            right: (0..2_000).map(|i| format!("right {}", i)).collect(),
+            budget: Duration::from_secs(1),
+        },
+        Corpus {
+            name: "repeated",
+            left: (0..5_000)
+                .map(|i| ["}", "", "    }"][i % 3].to_string())
+                .collect(),
+            right: (0..5_000)
+                .map(|i| ["}", "", "  }"][i % 3].to_string())
+                .collect(),
+            budget: Duration::from_secs(1),
+        },
+    ]
+}
+
+/// Multiplier of all budgets, read from `DIFF_UTILS_PERF_SCALE` environment variable, so slower
+/// machines or debug builds can relax them. Default: 1
+pub fn budget_scale() -> f64 {
+    std::env::var("DIFF_UTILS_PERF_SCALE")
+        .ok()
+        .and_then(|scale| scale.parse().ok())
+        .unwrap_or(1.0)
+}
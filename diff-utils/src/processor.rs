@@ -1,33 +1,47 @@
+use crate::line::{occurrences, Occurrence};
+use crate::ops::{Op, Recorder};
 use crate::Hunk;
 use crate::{Context, Line};
+use std::cmp::min;
 use std::io;
+use std::ops::Range;
 
+/// Turns the edit script into hunks. Operations are recorded first and grouped into hunks once
+/// the whole script is known, so the decision whether two changes share a hunk depends only on
+/// the number of unchanged lines between them.
 #[derive(Debug)]
-pub struct Processor<'a> {
-    pub(crate) text1: &'a [&'a str],
-    pub(crate) text2: &'a [&'a str],
+pub struct Processor<'t, 'a> {
+    pub(crate) text1: &'t [&'a str],
+    pub(crate) text2: &'t [&'a str],
+    pub(crate) occurrences1: Vec<Occurrence>,
+    pub(crate) occurrences2: Vec<Occurrence>,
 
     pub(crate) context_radius: usize,
-    pub(crate) inserted: usize,
-    pub(crate) removed: usize,
+    pub(crate) interhunk_lines: usize,
+    pub(crate) max_lines: Option<usize>,
 
-    pub(crate) context: Context<'a>,
+    pub(crate) recorder: Recorder,
     pub(crate) result: Vec<Hunk<'a>>,
-    pub(crate) size: usize,
 }
 
-impl<'a> Processor<'a> {
-    pub fn new(text1: &'a [&'a str], text2: &'a [&'a str], context_radius: usize) -> Self {
+impl<'t, 'a> Processor<'t, 'a> {
+    pub fn new(
+        text1: &'t [&'a str],
+        text2: &'t [&'a str],
+        context_radius: usize,
+        interhunk_lines: usize,
+    ) -> Self {
         Self {
             text1,
             text2,
+            occurrences1: occurrences(text1),
+            occurrences2: occurrences(text2),
 
             context_radius,
-            inserted: 0,
-            removed: 0,
-            size: 0,
+            interhunk_lines,
+            max_lines: None,
 
-            context: Context::default(),
+            recorder: Recorder::default(),
             result: Vec::new(),
         }
     }
@@ -37,119 +51,161 @@ impl<'a> Processor<'a> {
     }
 }
 
-impl<'a> Processor<'a> {
-    fn split_hunks(&mut self, i: impl Into<Option<usize>>) {
-        let diff = self
-            .size
-            .checked_sub(self.context_radius)
-            .unwrap_or_default();
-
-        let at = self.context.data.len() - diff;
-        let mut removed = self.context.data.split_off(at);
-        self.context.equaled -= diff;
+impl<'t, 'a> Processor<'t, 'a> {
+    fn unchanged(&self, i: usize, j: usize) -> Line<'a> {
+        Line::unchanged(i, j, self.text1[i]).with_occurrence(self.occurrences1[i])
+    }
 
-        if let Some(hunk) = self.context.create_hunk(self.removed, self.inserted) {
-            self.result.push(hunk);
+    fn push_unchanged(&self, context: &mut Context<'a>, old: usize, new: usize, len: usize) {
+        for (i, j) in (old..old + len).zip(new..new + len) {
+            context.push(self.unchanged(i, j));
         }
-
-        removed.pop_front();
-
-        self.removed += self.context.removed;
-        self.inserted += self.context.inserted;
-
-        self.context = Context::default();
-        let i = i.into();
-        self.context.start = i.map(|i| i - removed.len());
-        self.context.equaled += removed.len();
-        self.size = removed.len();
-        self.context.data.extend(removed.into_iter());
     }
-}
 
-impl<'a> diffs::Diff for Processor<'a> {
-    type Error = io::Error;
-
-    fn equal(&mut self, old: usize, _new: usize, len: usize) -> Result<(), Self::Error> {
-        self.size = 0;
+    fn push_change(&self, context: &mut Context<'a>, op: Op) {
+        match op {
+            Op::Equal { old, new, len } => self.push_unchanged(context, old, new, len),
+            Op::Delete { old, len, .. } => {
+                for i in old..old + len {
+                    context
+                        .push(Line::remove(i, self.text1[i]).with_occurrence(self.occurrences1[i]));
+                }
+            }
+            Op::Insert { new, len, .. } => {
+                for j in new..new + len {
+                    context
+                        .push(Line::insert(j, self.text2[j]).with_occurrence(self.occurrences2[j]));
+                }
+            }
+            Op::Replace {
+                old,
+                old_len,
+                new,
+                new_len,
+            } => {
+                for (i, j) in (old..old + old_len).zip(new..new + old_len) {
+                    let j = if j < (new + new_len) { Some(j) } else { None };
+                    context.push(
+                        Line::replace_remove(i, j, self.text1[i])
+                            .with_occurrence(self.occurrences1[i]),
+                    );
+                }
 
-        if self.context.start.is_none() {
-            self.context.start = Some(old);
+                for (j, i) in (new..new + new_len).zip(old..old + new_len) {
+                    let i = if i < (old + old_len) { Some(i) } else { None };
+                    context.push(
+                        Line::replace_insert(i, j, self.text2[j])
+                            .with_occurrence(self.occurrences2[j]),
+                    );
+                }
+            }
         }
+    }
 
-        for (i, j) in (old..old + len).zip(_new.._new + len) {
-            if !self.context.changed {
-                self.context
-                    .data
-                    .push_back(Line::unchanged(i, j, self.text1[i]));
-                if self.size < self.context_radius {
-                    self.context.equaled += 1;
-                    self.size += 1;
-                } else {
-                    self.context.data.pop_front();
-                    if let Some(ref mut start) = self.context.start {
-                        *start += 1;
+    /// Finds which operations make up each hunk, before any line of it is stored.
+    fn spans(&self, ops: &[Op]) -> Vec<Span> {
+        let mut spans = Vec::new();
+        let mut current: Option<Span> = None;
+
+        for (idx, op) in ops.iter().enumerate() {
+            if let Op::Equal { len, .. } = *op {
+                if let Some(mut span) = current.take() {
+                    let is_last = idx + 1 == ops.len();
+                    if !is_last && len <= self.interhunk_lines {
+                        // Next change is close enough, so both of them share the hunk.
+                        span.ops.end = idx + 1;
+                        current = Some(span);
+                    } else {
+                        span.trailing = min(len, self.context_radius);
+                        spans.push(span);
                     }
                 }
+                continue;
             }
 
-            if self.context.changed {
-                /*
-                We want * 2 in case next hunk would be adjacent to the current one.
-                 */
-                if self.size < self.context_radius * 2 {
-                    self.context
-                        .data
-                        .push_back(Line::unchanged(i, j, self.text1[i]));
-                    self.context.equaled += 1;
-                    self.size += 1;
-                } else {
-                    // But if there are more unchanged lines between two changes than context_radius * 2,
-                    // then we want to split hunk into smaller.
-
-                    self.split_hunks(i);
-
-                    self.context
-                        .data
-                        .push_back(Line::unchanged(i, j, self.text1[i]));
-                    self.size += 1;
-                    self.context.equaled += 1;
+            let mut span = current.take().unwrap_or_else(|| {
+                // Trailing context of the previous hunk isn't repeated, so hunks never overlap.
+                let taken = match spans.last() {
+                    Some(Span { ops, trailing, .. }) if ops.end + 1 == idx => *trailing,
+                    _ => 0,
+                };
+                let leading = match idx.checked_sub(1).map(|prev| ops[prev]) {
+                    Some(Op::Equal { len, .. }) => min(len - taken, self.context_radius),
+                    _ => 0,
+                };
+                Span {
+                    ops: idx..idx,
+                    leading,
+                    trailing: 0,
                 }
-            }
+            });
+            span.ops.end = idx + 1;
+            current = Some(span);
         }
 
-        Ok(())
+        spans.extend(current);
+        spans
     }
 
-    fn delete(&mut self, old: usize, len: usize, _new: usize) -> Result<(), Self::Error> {
-        self.size = 0;
-        if self.context.start.is_none() {
-            self.context.start = Some(old);
+    fn split_hunks(&mut self, ops: &[Op]) -> io::Result<()> {
+        let spans = self.spans(ops);
+        let sizes: Vec<usize> = spans.iter().map(|span| span.size(ops)).collect();
+        if let Some(max_lines) = self.max_lines {
+            let total = sizes.iter().sum::<usize>();
+            if total > max_lines {
+                return Err(io::Error::other(format!(
+                    "result of {} lines exceeds the limit of {}",
+                    total, max_lines
+                )));
+            }
         }
 
-        for i in old..old + len {
-            self.context.data.push_back(Line::remove(i, self.text1[i]));
+        self.result.reserve_exact(spans.len());
+        for (span, size) in spans.into_iter().zip(sizes) {
+            let (old, new) = ops[span.ops.start].start();
+            let (old, new) = (old - span.leading, new - span.leading);
+            let mut current = Context::with_capacity(old, new, size);
+            self.push_unchanged(&mut current, old, new, span.leading);
+            for op in &ops[span.ops.clone()] {
+                self.push_change(&mut current, *op);
+            }
+            if let Some(Op::Equal { old, new, .. }) = ops.get(span.ops.end) {
+                self.push_unchanged(&mut current, *old, *new, span.trailing);
+            }
+            self.result.push(current.create_hunk());
         }
+        Ok(())
+    }
+}
 
-        self.context.changed = true;
-        self.context.removed += len;
+/// Operations of one hunk, with the unchanged lines before and after them.
+#[derive(Debug)]
+struct Span {
+    ops: Range<usize>,
+    leading: usize,
+    trailing: usize,
+}
 
-        Ok(())
+impl Span {
+    /// Number of lines in the hunk.
+    fn size(&self, ops: &[Op]) -> usize {
+        self.leading + self.trailing + ops[self.ops.clone()].iter().map(Op::lines).sum::<usize>()
     }
+}
 
-    fn insert(&mut self, old: usize, new: usize, new_len: usize) -> Result<(), Self::Error> {
-        self.size = 0;
-        if self.context.start.is_none() {
-            self.context.start = Some(old);
-        }
+impl<'t, 'a> diffs::Diff for Processor<'t, 'a> {
+    type Error = io::Error;
 
-        for i in new..new + new_len {
-            self.context.data.push_back(Line::insert(i, self.text2[i]));
-        }
+    fn equal(&mut self, old: usize, new: usize, len: usize) -> Result<(), Self::Error> {
+        self.recorder.equal(old, new, len)
+    }
 
-        self.context.changed = true;
-        self.context.inserted += new_len;
+    fn delete(&mut self, old: usize, len: usize, new: usize) -> Result<(), Self::Error> {
+        self.recorder.delete(old, len, new)
+    }
 
-        Ok(())
+    fn insert(&mut self, old: usize, new: usize, new_len: usize) -> Result<(), Self::Error> {
+        self.recorder.insert(old, new, new_len)
     }
 
     fn replace(
@@ -159,35 +215,11 @@ impl<'a> diffs::Diff for Processor<'a> {
         new: usize,
         new_len: usize,
     ) -> Result<(), Self::Error> {
-        self.size = 0;
-        if self.context.start.is_none() {
-            self.context.start = Some(old);
-        }
-
-        for (i, j) in (old..old + old_len).zip(new..new + old_len) {
-            let j = if j < (new + new_len) { Some(j) } else { None };
-            self.context
-                .data
-                .push_back(Line::replace_remove(i, j, self.text1[i]));
-        }
-
-        for (j, i) in (new..new + new_len).zip(old..old + new_len) {
-            let i = if i < (old + old_len) { Some(i) } else { None };
-            self.context
-                .data
-                .push_back(Line::replace_insert(i, j, self.text2[j]));
-        }
-
-        self.context.changed = true;
-        self.context.removed += old_len;
-        self.context.inserted += new_len;
-
-        Ok(())
+        self.recorder.replace(old, old_len, new, new_len)
     }
 
     fn finish(&mut self) -> Result<(), Self::Error> {
-        self.split_hunks(None);
-
-        Ok(())
+        let ops = std::mem::take(&mut self.recorder.ops);
+        self.split_hunks(&ops)
     }
 }
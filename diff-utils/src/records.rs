@@ -0,0 +1,86 @@
+//! Record (paragraph) mode of the comparison.
+//!
+//! Lines are grouped into records separated by lines recognized by the user, e.g. blank ones.
+//! Whole records are compared first, so a changed paragraph doesn't get matched line by line with
+//! unrelated paragraphs which happen to share a few common lines. Only ranges of changed records
+//! are then refined with a regular line diff.
+
+use crate::ops::{Op, Recorder};
+use std::io;
+use std::ops::Range;
+
+pub(crate) fn diff(
+    recorder: &mut Recorder,
+    left: &[&str],
+    right: &[&str],
+    is_separator: &dyn Fn(&str) -> bool,
+) -> io::Result<()> {
+    let left_records = split(left, is_separator);
+    let right_records = split(right, is_separator);
+    let left_keys = left_records
+        .iter()
+        .map(|r| &left[r.clone()])
+        .collect::<Vec<_>>();
+    let right_keys = right_records
+        .iter()
+        .map(|r| &right[r.clone()])
+        .collect::<Vec<_>>();
+
+    let mut records = Recorder::default();
+    diffs::patience::diff(
+        &mut records,
+        &left_keys,
+        0,
+        left_keys.len(),
+        &right_keys,
+        0,
+        right_keys.len(),
+    )?;
+
+    for op in records.result() {
+        let (old, old_len, new, new_len) = match op {
+            Op::Equal { old, new, len } => (old, len, new, len),
+            Op::Delete { old, len, new } => (old, len, new, 0),
+            Op::Insert { old, new, len } => (old, 0, new, len),
+            Op::Replace {
+                old,
+                old_len,
+                new,
+                new_len,
+            } => (old, old_len, new, new_len),
+        };
+        let old = lines(&left_records, old..old + old_len, left.len());
+        let new = lines(&right_records, new..new + new_len, right.len());
+        match op {
+            Op::Equal { .. } => diffs::Diff::equal(recorder, old.start, new.start, old.len())?,
+            _ => diffs::patience::diff(
+                recorder, left, old.start, old.end, right, new.start, new.end,
+            )?,
+        }
+    }
+    Ok(())
+}
+
+/// Every separator is a record on its own, lines between them are grouped together.
+fn split(text: &[&str], is_separator: &dyn Fn(&str) -> bool) -> Vec<Range<usize>> {
+    let mut records: Vec<Range<usize>> = Vec::new();
+    for (i, line) in text.iter().enumerate() {
+        match records.last_mut() {
+            Some(last) if !is_separator(line) && !is_separator(text[last.start]) => {
+                last.end = i + 1
+            }
+            _ => records.push(i..i + 1),
+        }
+    }
+    records
+}
+
+/// Range of lines covered by the range of records.
+fn lines(records: &[Range<usize>], range: Range<usize>, len: usize) -> Range<usize> {
+    let start = records.get(range.start).map_or(len, |r| r.start);
+    if range.is_empty() {
+        start..start
+    } else {
+        start..records[range.end - 1].end
+    }
+}
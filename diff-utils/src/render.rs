@@ -0,0 +1,75 @@
+/*
+
+Here is code for options shared by all the ways of rendering a result
+
+*/
+
+/// Options common to [`DisplayOptions`](struct.DisplayOptions.html) and
+/// [`PatchOptions`](struct.PatchOptions.html), so the diff shown by an assertion and the patch
+/// generated alongside it can't get out of sync.
+///
+/// Both [`DisplayOptions::render`](struct.DisplayOptions.html#method.render) and
+/// [`PatchOptions::render`](struct.PatchOptions.html#method.render) take it, and
+/// [`CompareResult::patch_string`](struct.CompareResult.html#method.patch_string) uses its labels
+/// too. Context radius belongs to the [`Comparison`](struct.Comparison.html), because it shapes the
+/// hunks themselves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RenderOptions<'a> {
+    /// Number of the first line, see [`DisplayOptions::offset`](struct.DisplayOptions.html#structfield.offset).
+    ///
+    /// Default value: 1
+    pub offset: usize,
+    /// Name of the left/old file in patch headers.
+    ///
+    /// Default value: `expected`
+    pub left_label: &'a str,
+    /// Name of the right/new file in patch headers.
+    ///
+    /// Default value: `actual`
+    pub right_label: &'a str,
+}
+
+impl<'a> RenderOptions<'a> {
+    /// Sets [`offset`](#structfield.offset)
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Sets [`left_label`](#structfield.left_label) and [`right_label`](#structfield.right_label)
+    pub fn labels(mut self, left_label: &'a str, right_label: &'a str) -> Self {
+        self.left_label = left_label;
+        self.right_label = right_label;
+        self
+    }
+}
+
+impl<'a> Default for RenderOptions<'a> {
+    fn default() -> Self {
+        Self {
+            offset: 1,
+            left_label: "expected",
+            right_label: "actual",
+        }
+    }
+}
+
+#[cfg(all(test, feature = "display-plain", feature = "patch"))]
+mod tests {
+    use super::*;
+    use crate::{Comparison, DisplayOptions, PatchOptions, Plain};
+
+    #[test]
+    fn display_and_patch_agree() {
+        let render = RenderOptions::default().offset(100).labels("old", "new");
+        let result = Comparison::new(&["a", "b", "c"], &["a", "B", "c"]).compare_infallible();
+
+        let display = result
+            .display(DisplayOptions::default().painter(&Plain).render(&render))
+            .to_string();
+        let patch = result.patch_string(&render, PatchOptions::default());
+        assert!(display.contains("@@ -100,3 +100,3 @@"));
+        assert!(patch.starts_with("--- old\n+++ new\n@@ -100,3 +100,3 @@\n"));
+    }
+}
@@ -0,0 +1,17 @@
+#![cfg(feature = "fuzz")]
+use diff_utils::fuzz;
+
+#[test]
+fn round_trip() {
+    for seed in 0..2000 {
+        let len = (seed % 64) as usize;
+        fuzz::round_trip(&fuzz::random_bytes(seed, len));
+    }
+}
+
+#[test]
+fn separator() {
+    let (left, right) = fuzz::texts(&[1, 2, 0xff, 3]);
+    assert_eq!(left, vec!["a", "b"]);
+    assert_eq!(right, vec!["c"]);
+}
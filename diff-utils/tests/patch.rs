@@ -1,10 +1,11 @@
 #![cfg(feature = "patch")]
-use anyhow::Result;
-use chrono::{DateTime, Local};
+use anyhow::{ensure, Context, Result};
 use diff_utils::{Comparison, PatchOptions};
 use itertools::Itertools;
 use std::borrow::Cow;
 use std::io::Write;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 #[test]
 fn test() -> Result<()> {
@@ -27,8 +28,6 @@ fn test() -> Result<()> {
         let comparison = Comparison::new(&expected_lines, &actual_lines).compare()?;
 
         let dt = "2020-06-27 18:10:03 +0200";
-        let datetime: DateTime<Local> = dt.parse()?;
-        let dt = datetime.format("%F %T %z");
 
         let left_name = Cow::Borrowed("left");
         let right_name = Cow::Borrowed("right");
@@ -42,11 +41,10 @@ fn test() -> Result<()> {
 
         std::fs::File::create(&new_path).and_then(|mut file| write!(file, "{}", &new))?;
 
-        use std::process::Command;
         let expected_path = expected_path.display().to_string();
         let actual_path = actual_path.display().to_string();
         let diff_cmd = Command::new("diff")
-            .args(&["-u", expected_path.as_str(), actual_path.as_str()])
+            .args(["-u", expected_path.as_str(), actual_path.as_str()])
             .output()?;
 
         let patch = diff_cmd.stdout.as_slice();
@@ -71,3 +69,140 @@ fn test() -> Result<()> {
 
     Ok(())
 }
+
+const ROUND_TRIP_CASES: &[(&str, &str)] = &[
+    ("a\nb\nc\n", "a\nB\nc\n"),
+    ("a\nb\nc\n", "x\na\nb\nc\n"),
+    ("a\nb\nc\n", "a\nb\nc\nx\n"),
+    ("a\nb\nc\n", "b\nc\n"),
+    ("a\nb\nc\n", "a\nb\n"),
+    ("a\nb\nc\nd\ne\nf\ng\nh\n", "a\nc\nd\nX\ne\nf\nh\nY\n"),
+    ("a\nb\nc\nd\ne\nf\ng\nh\ni\nj\n", "b\nc\nd\ne\nf\ng\nh\ni\n"),
+    ("1\n2\n3\n4\n5\n", "1\n2\nx\ny\nz\n3\n4\n5\n"),
+    ("", "a\nb\n"),
+    ("a\nb\n", ""),
+];
+
+#[test]
+fn round_trip_with_gnu_patch() -> Result<()> {
+    let dt = "2020-06-27 18:10:03 +0200";
+
+    for (case, (left, right)) in ROUND_TRIP_CASES.iter().enumerate() {
+        for context_radius in 0..4 {
+            let left_lines = left.lines().collect::<Vec<_>>();
+            let right_lines = right.lines().collect::<Vec<_>>();
+            let comparison = Comparison {
+                context_radius,
+                ..Comparison::new(&left_lines, &right_lines)
+            }
+            .compare()?;
+            let patch = comparison
+                .patch(
+                    Cow::Borrowed("left"),
+                    &dt,
+                    Cow::Borrowed("right"),
+                    &dt,
+                    PatchOptions::default(),
+                )
+                .to_string();
+
+            let out = apply_with_gnu_patch(left, &patch).with_context(|| {
+                format!("case {} with radius {}:\n{}", case, context_radius, patch)
+            })?;
+            assert_eq!(
+                &out, right,
+                "wrong reconstruction for case {} with radius {}:\n{}",
+                case, context_radius, patch
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Pairs of files with Windows line endings or without the new line at the end.
+const NEWLINE_CASES: &[(&str, &str)] = &[
+    ("a\r\nb\r\nc\r\n", "a\r\nB\r\nc\r\n"),
+    ("a\nb", "a\nc"),
+    ("a\nb\n", "a\nb\nc"),
+    ("a\nb\nc", "a\nb\n"),
+    ("a\r\nb", "a\r\nc"),
+    ("", "a"),
+    ("a\nb\nc\nd\ne\nf", "a\nB\nc\nd\ne\nF"),
+];
+
+#[test]
+fn newline_fidelity_with_gnu_patch() -> Result<()> {
+    for (case, (left, right)) in NEWLINE_CASES.iter().enumerate() {
+        for context_radius in 0..3 {
+            let left_lines = left.lines().collect::<Vec<_>>();
+            let right_lines = right.lines().collect::<Vec<_>>();
+            let comparison = Comparison {
+                context_radius,
+                ..Comparison::new(&left_lines, &right_lines)
+            }
+            .compare()?;
+            let no_newline = |text: &str| !text.is_empty() && !text.ends_with('\n');
+            let options = PatchOptions::default()
+                .crlf(left.contains("\r\n"))
+                .left_no_newline(no_newline(left))
+                .right_no_newline(no_newline(right));
+            let patch = comparison
+                .patch(
+                    Cow::Borrowed("left"),
+                    &"",
+                    Cow::Borrowed("right"),
+                    &"",
+                    options,
+                )
+                .to_string();
+
+            let out = apply_with_gnu_patch(left, &patch).with_context(|| {
+                format!("case {} with radius {}:\n{:?}", case, context_radius, patch)
+            })?;
+            assert_eq!(
+                &out, right,
+                "wrong reconstruction for case {} with radius {}:\n{:?}",
+                case, context_radius, patch
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Applies the patch to the `left` text with GNU patch and returns the result. Fails also if
+/// the patch applies only at an offset or with fuzz.
+fn apply_with_gnu_patch(left: &str, patch: &str) -> Result<String> {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    let dir = std::env::temp_dir().join(format!(
+        "diff-utils-gnu-patch-{}-{}",
+        std::process::id(),
+        CALLS.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&dir)?;
+
+    let left_path = dir.join("left");
+    let patch_path = dir.join("patch");
+    let out_path = dir.join("out");
+    std::fs::write(&left_path, left)?;
+    std::fs::write(&patch_path, patch)?;
+
+    let output = Command::new("patch")
+        .arg("--fuzz=0")
+        .arg("--binary")
+        .arg("-o")
+        .arg(&out_path)
+        .arg(&left_path)
+        .arg(&patch_path)
+        .output()?;
+    let log = String::from_utf8_lossy(&output.stdout);
+    ensure!(output.status.success(), "patch failed:\n{}", log);
+    ensure!(
+        !log.contains("offset") && !log.contains("fuzz"),
+        "patch had to guess positions:\n{}",
+        log
+    );
+
+    let out = std::fs::read_to_string(&out_path)?;
+    std::fs::remove_dir_all(&dir)?;
+    Ok(out)
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "perf-test")]
+use diff_utils::perf;
+
+/// Budgets are meant for optimized builds, run it with:
+/// `cargo test -p diff_utils --release --features perf-test --test perf`.
+/// `DIFF_UTILS_PERF_SCALE` relaxes them, e.g. for slower CI runners.
+#[test]
+#[cfg_attr(debug_assertions, ignore = "budgets are meant for optimized builds")]
+fn within_budgets() {
+    let scale = perf::budget_scale();
+    for corpus in perf::corpora() {
+        let budget = corpus.budget.mul_f64(scale);
+        let (elapsed, _) = corpus.run();
+        assert!(
+            elapsed <= budget,
+            "{} took {:?}, budget is {:?}",
+            corpus.name,
+            elapsed,
+            budget
+        );
+    }
+}
+
+#[test]
+fn corpora_produce_hunks() {
+    let hunks = perf::corpora()
+        .iter()
+        .map(|corpus| (corpus.name, corpus.run().1 > 0))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hunks,
+        vec![
+            ("equal", false),
+            ("scattered", true),
+            ("unrelated", true),
+            ("repeated", true)
+        ]
+    );
+}
@@ -0,0 +1,197 @@
+use diff_utils::Newline;
+use std::borrow::Cow;
+use std::ffi::{OsStr, OsString};
+
+/// Content which can be compared line by line by all the macros of this crate.
+///
+/// It is implemented for string types, which are split with [`str::lines`], and for slices and
+/// vectors of lines, which are taken as they are. Strings, bytes and OS strings can be split with
+/// other separators too, see [`Newline`](enum.Newline.html). Bytes and OS strings, e.g. a command output, are
+/// converted lossily: invalid UTF-8 becomes `U+FFFD` and the report points it out. Implement it for your own types to pass them
+/// to the macros directly:
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// use diff_assert::AsLines;
+/// use std::borrow::Cow;
+///
+/// struct Csv(Vec<Vec<&'static str>>);
+///
+/// impl AsLines for Csv {
+///     fn as_lines(&self) -> Vec<Cow<'_, str>> {
+///         self.0.iter().map(|row| Cow::Owned(row.join(","))).collect()
+///     }
+/// }
+///
+/// # fn main() {
+/// assert_diff!("a,b\nc,d", Csv(vec![vec!["a", "b"], vec!["c", "d"]]));
+/// # }
+/// ```
+pub trait AsLines {
+    /// Lines of the content, without line terminators
+    fn as_lines(&self) -> Vec<Cow<'_, str>>;
+
+    /// Lines of the content split with the given separator. Content which is not a single text,
+    /// e.g. slices of lines, ignores it. By default it returns [`as_lines`](#tymethod.as_lines).
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        let _ = newline;
+        self.as_lines()
+    }
+}
+
+impl AsLines for str {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_lines_with(Newline::Lines)
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        newline.split(self).into_iter().map(Cow::Borrowed).collect()
+    }
+}
+
+impl AsLines for String {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_str().as_lines()
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        self.as_str().as_lines_with(newline)
+    }
+}
+
+impl AsLines for Cow<'_, str> {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_ref().as_lines()
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        self.as_ref().as_lines_with(newline)
+    }
+}
+
+impl AsLines for [String] {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.iter()
+            .map(|line| Cow::Borrowed(line.as_str()))
+            .collect()
+    }
+}
+
+impl AsLines for [&str] {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.iter().map(|line| Cow::Borrowed(*line)).collect()
+    }
+}
+
+impl AsLines for Vec<String> {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_slice().as_lines()
+    }
+}
+
+impl AsLines for Vec<&str> {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_slice().as_lines()
+    }
+}
+
+impl AsLines for [u8] {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_lines_with(Newline::Lines)
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        lossy_lines(String::from_utf8_lossy(self), newline)
+    }
+}
+
+impl<const N: usize> AsLines for [u8; N] {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_slice().as_lines()
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        self.as_slice().as_lines_with(newline)
+    }
+}
+
+impl AsLines for Vec<u8> {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_slice().as_lines()
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        self.as_slice().as_lines_with(newline)
+    }
+}
+
+impl AsLines for OsStr {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_lines_with(Newline::Lines)
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        lossy_lines(self.to_string_lossy(), newline)
+    }
+}
+
+impl AsLines for OsString {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.as_os_str().as_lines()
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        self.as_os_str().as_lines_with(newline)
+    }
+}
+
+fn lossy_lines(text: Cow<'_, str>, newline: Newline) -> Vec<Cow<'_, str>> {
+    match text {
+        Cow::Borrowed(text) => text.as_lines_with(newline),
+        Cow::Owned(text) => newline
+            .split(&text)
+            .into_iter()
+            .map(|line| Cow::Owned(line.to_owned()))
+            .collect(),
+    }
+}
+
+/// Data made of fixed-length records without any line structure, e.g. mainframe-style exports.
+/// Every `record_len` bytes become a line, so records are compared as a whole and changed fields
+/// are highlighted within them. The last record may be shorter.
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// use diff_assert::FixedWidth;
+///
+/// # fn main() {
+/// let expected = "0001SMITH     0002JONES     ";
+/// assert_diff!(FixedWidth(expected, 14), "0001SMITH     \n0002JONES     ");
+/// assert!(try_diff!(FixedWidth(expected, 14), FixedWidth("0001SMITH     0002JOHNS     ", 14)).is_err());
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FixedWidth<T>(
+    /// Content of all the records
+    pub T,
+    /// Length of a single record, in bytes
+    pub usize,
+);
+
+impl<T: AsRef<[u8]>> AsLines for FixedWidth<T> {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        self.0
+            .as_ref()
+            .chunks(self.1.max(1))
+            .map(String::from_utf8_lossy)
+            .collect()
+    }
+}
+
+impl<T: AsLines + ?Sized> AsLines for &T {
+    fn as_lines(&self) -> Vec<Cow<'_, str>> {
+        (**self).as_lines()
+    }
+
+    fn as_lines_with(&self, newline: Newline) -> Vec<Cow<'_, str>> {
+        (**self).as_lines_with(newline)
+    }
+}
@@ -0,0 +1,97 @@
+use diff_utils::display_width;
+
+/// Inputs wider than that, in terminal columns, are rendered as regular hunks.
+const MAX_WIDTH: usize = 80;
+
+/// Compact report for short values of at most `max_lines` lines:
+/// ```text
+/// expected: "foo bar"
+/// actual:   "foo baz"
+///                  ^
+/// ```
+/// Lines of longer values are joined with escaped new lines. Returns `None` if any of the inputs
+/// doesn't fit.
+pub(crate) fn render(
+    expected: &[&str],
+    actual: &[&str],
+    msg_fmt: &str,
+    max_lines: usize,
+) -> Option<String> {
+    let fits = |lines: &[&str]| {
+        (1..=max_lines.max(1)).contains(&lines.len())
+            && lines.iter().all(|line| display_width(line) <= MAX_WIDTH)
+    };
+    if !fits(expected) || !fits(actual) {
+        return None;
+    }
+    let (expected, actual) = (expected.join("\n"), actual.join("\n"));
+
+    let prefix_len: usize = expected
+        .chars()
+        .zip(actual.chars())
+        .take_while(|(e, a)| e == a)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    // Escaped prefix with opening quote, but without the closing one.
+    let column = display_width(&format!("{:?}", &actual[..prefix_len])) - 1;
+
+    Some(format!(
+        "\n{}\n\nexpected: {:?}\nactual:   {:?}\n          {:column$}^\n",
+        msg_fmt,
+        expected,
+        actual,
+        "",
+        column = column
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn caret_under_first_difference() {
+        assert_eq!(
+            render(&["foo bar"], &["foo baz"], "Found differences", 1).unwrap(),
+            "\nFound differences\n\n\
+             expected: \"foo bar\"\n\
+             actual:   \"foo baz\"\n\
+             \x20                ^\n"
+        );
+    }
+
+    #[test]
+    fn caret_after_escaped_chars() {
+        let report = render(&["a\tb"], &["a\tc"], "", 1).unwrap();
+        assert!(report.ends_with("actual:   \"a\\tc\"\n              ^\n"));
+    }
+
+    #[test]
+    fn caret_after_wide_chars() {
+        let report = render(&["東京 foo"], &["東京 bar"], "", 1).unwrap();
+        assert!(report.ends_with("actual:   \"東京 bar\"\n                ^\n"));
+        let report = render(&["🙂a"], &["🙂b"], "", 1).unwrap();
+        assert!(report.ends_with("actual:   \"🙂b\"\n             ^\n"));
+    }
+
+    #[test]
+    fn multiline_or_long_inputs() {
+        assert!(render(&["foo", "bar"], &["foo"], "", 1).is_none());
+        assert!(render(&[], &["foo"], "", 1).is_none());
+        let long = "x".repeat(MAX_WIDTH + 1);
+        assert!(render(&[&long], &["foo"], "", 1).is_none());
+        let wide = "字".repeat(MAX_WIDTH / 2 + 1);
+        assert!(render(&[&wide], &["foo"], "", 1).is_none());
+    }
+
+    #[test]
+    fn a_few_lines() {
+        let report = render(&["foo", "bar"], &["foo", "baz"], "", 2).unwrap();
+        assert!(report.ends_with(
+            "expected: \"foo\\nbar\"\n\
+             actual:   \"foo\\nbaz\"\n\
+             \x20                 ^\n"
+        ));
+        assert!(render(&["a", "b", "c"], &["a"], "", 2).is_none());
+    }
+}
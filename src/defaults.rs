@@ -0,0 +1,37 @@
+use diff_utils::DisplayOptions;
+use std::sync::RwLock;
+
+static DEFAULT_DISPLAY_OPTIONS: RwLock<Option<DisplayOptions<'static>>> = RwLock::new(None);
+
+/// Sets display options used by all the macros from now on, in every thread. Only `msg_fmt` is
+/// always taken from the macro call.
+///
+/// Unlike `colored::control::set_override`, the painter can be chosen without affecting the
+/// environment of other code running in parallel, e.g. in `cargo test`.
+///
+/// # Example
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// use diff_assert::{set_default_display_options, DisplayOptions, Plain};
+///
+/// # fn main() {
+/// set_default_display_options(DisplayOptions::default().offset(1).painter(&Plain));
+/// let err = try_diff!("foo\nbar", "foo").unwrap_err();
+/// assert!(err.to_string().contains("002      -bar"));
+/// # }
+/// ```
+pub fn set_default_display_options(options: DisplayOptions<'static>) {
+    *DEFAULT_DISPLAY_OPTIONS
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(options);
+}
+
+/// Display options used by all the macros. Unless changed by
+/// [`set_default_display_options`](fn.set_default_display_options.html), lines are counted from 0
+/// and the default painter of `diff_utils` is used.
+pub fn default_display_options() -> DisplayOptions<'static> {
+    DEFAULT_DISPLAY_OPTIONS
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .unwrap_or_else(|| DisplayOptions::default().offset(0))
+}
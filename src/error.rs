@@ -0,0 +1,79 @@
+use std::error::Error;
+use std::fmt;
+
+/// Error returned by `try_*` macros when compared objects differ.
+///
+/// Its [`Display`](std::fmt::Display) implementation prints the whole report, exactly the same as
+/// assertion macros do when they panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffError {
+    message: String,
+}
+
+impl DiffError {
+    pub(crate) fn new(message: String) -> Self {
+        Self { message }
+    }
+
+    /// Report without any ANSI escape codes, regardless of the terminal and global color settings.
+    /// Use it whenever the message is compared, e.g. in doctests.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[macro_use] extern crate diff_assert;
+    /// # fn main() {
+    /// let err = try_diff!("foo\nbar", "foo").unwrap_err();
+    /// assert!(err.to_plain_string().contains("001      -bar"));
+    /// # }
+    /// ```
+    pub fn to_plain_string(&self) -> String {
+        strip_ansi(&self.message)
+    }
+
+    /// Report with ANSI escape color codes, if the [`colored`](https://docs.rs/colored) crate
+    /// decides to emit them (it checks if output is a terminal, `CLICOLOR` variables etc.).
+    pub fn to_colored_string(&self) -> String {
+        self.message.clone()
+    }
+}
+
+impl fmt::Display for DiffError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for DiffError {}
+
+/// Removes ANSI CSI sequences, like `\u{1b}[1;31m`.
+fn strip_ansi(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' && chars.peek() == Some(&'[') {
+            chars.next();
+            // Parameters and intermediate bytes, then the final byte in range `@`..=`~`.
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_string() {
+        let err =
+            DiffError::new("\u{1b}[31m001      \u{1b}[1m-\u{1b}[0m\u{1b}[31mfoo\u{1b}[0m".into());
+        assert_eq!(err.to_plain_string(), "001      -foo");
+        assert_eq!(err.to_colored_string(), err.to_string());
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{CompareResult, DiffAssertOptions, DiffError};
+use std::sync::{Arc, RwLock};
+
+pub(crate) type FailureHook = Arc<dyn Fn(&Failure) + Send + Sync>;
+
+static FAILURE_HOOK: RwLock<Option<FailureHook>> = RwLock::new(None);
+
+/// Failed comparison passed to failure hooks, see
+/// [`set_failure_hook`](fn.set_failure_hook.html).
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Failure<'a> {
+    /// Expected lines, after all normalizations
+    pub expected: &'a [&'a str],
+    /// Actual lines, after all normalizations
+    pub actual: &'a [&'a str],
+    /// Hunks of the diff. `None` for macros which don't compute one, like
+    /// [`assert_subsequence!`](macro.assert_subsequence.html)
+    pub result: Option<&'a CompareResult<'a>>,
+    /// The whole report, the same as returned by `try_*` macros
+    pub error: &'a DiffError,
+}
+
+/// Sets a callback invoked by all the macros on every failed comparison from now on, in every
+/// thread, e.g. to publish metrics or upload artifacts. Assertion macros call it before they
+/// panic. A hook of a single call can be added with
+/// [`DiffAssertOptions::on_failure`](struct.DiffAssertOptions.html#method.on_failure), and it runs
+/// after the global one.
+///
+/// # Example
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// use diff_assert::set_failure_hook;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static CHANGED: AtomicUsize = AtomicUsize::new(0);
+///
+/// # fn main() {
+/// set_failure_hook(|failure| {
+///     let hunks = failure.result.map_or(0, |result| result.hunks().len());
+///     CHANGED.fetch_add(hunks, Ordering::SeqCst);
+/// });
+/// assert!(try_diff!("foo\nbar", "foo\nbaz").is_err());
+/// assert_eq!(CHANGED.load(Ordering::SeqCst), 1);
+/// # }
+/// ```
+pub fn set_failure_hook(hook: impl Fn(&Failure) + Send + Sync + 'static) {
+    *FAILURE_HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(Arc::new(hook));
+}
+
+/// Removes the callback set by [`set_failure_hook`](fn.set_failure_hook.html).
+pub fn clear_failure_hook() {
+    *FAILURE_HOOK
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+}
+
+/// Runs the global hook and the one of the call, then returns the error.
+pub(crate) fn fail(
+    options: &DiffAssertOptions,
+    expected: &[&str],
+    actual: &[&str],
+    result: Option<&CompareResult>,
+    error: DiffError,
+) -> Result<(), DiffError> {
+    let global = FAILURE_HOOK
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone();
+    if global.is_some() || options.on_failure.is_some() {
+        let failure = Failure {
+            expected,
+            actual,
+            result,
+            error: &error,
+        };
+        for hook in global.iter().chain(&options.on_failure) {
+            hook(&failure);
+        }
+    }
+    Err(error)
+}
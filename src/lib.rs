@@ -33,7 +33,7 @@
 //! ```
 //!
 //! Another possibility is to use [`try_diff!`](macro.try_diff.html) macro if you don't want to panic.
-//! It returns nice `Result<(), String>` instead.
+//! It returns nice `Result<(), DiffError>` instead.
 //! ```rust
 //! # #[macro_use] extern crate diff_assert;
 //! let expected = r#"foo
@@ -52,9 +52,89 @@
 //! [`Debug`](std::fmt::Debug) format outputs. It is quite handy for testing intermediate outputs.
 //! * [`assert_dbg!`](macro.assert_dbg.html)
 //! * [`try_dbg!`](macro.try_dbg.html)
+//!
+//! When only some key lines matter, e.g. in noisy logs, you can check that they appear in the
+//! right order, but not necessarily next to each other:
+//! * [`assert_subsequence!`](macro.assert_subsequence.html)
+//! * [`try_subsequence!`](macro.try_subsequence.html)
+//!
+//! If the order of lines doesn't matter at all, e.g. for query results, there are:
+//! * [`assert_same_lines!`](macro.assert_same_lines.html)
+//! * [`try_same_lines!`](macro.try_same_lines.html)
+//!
+//! or, to see missing and extra lines as a regular diff of both sides sorted:
+//! * [`assert_diff_sorted!`](macro.assert_diff_sorted.html)
+//! * [`try_diff_sorted!`](macro.try_diff_sorted.html)
+//!
+//! When a small amount of variability is fine, the diff can be limited to a number of lines:
+//! * [`assert_diff_within!`](macro.assert_diff_within.html)
+//! * [`try_diff_within!`](macro.try_diff_within.html)
+//!
+//! Data files without any line structure can be compared record by record with
+//! [`FixedWidth`](struct.FixedWidth.html).
+//!
+//! Every macro accepts extra options after a semicolon, see [`DiffAssertOptions`](struct.DiffAssertOptions.html):
+//! ```rust,should_panic
+//! # #[macro_use] extern crate diff_assert;
+//! assert_diff!("Foo\nbar", "foo\nbaz", "Here is an optional message"; ignore_case, context = 8);
+//! ```
+//!
+//! Styling and line numbering of all reports can be changed globally, in a thread-safe way, with
+//! [`set_default_display_options`](fn.set_default_display_options.html).
+//!
+//! Reports can be embedded in other assertion libraries with
+//! [`diff_string`](fn.diff_string.html), which returns them without panicking.
+//!
+//! Every failed comparison can also trigger a side effect, like publishing metrics, with
+//! [`set_failure_hook`](fn.set_failure_hook.html) or the `on_failure` option of a single call.
+//!
+//! Fixed English fragments of reports, like the default `Found differences` message, can be
+//! localized or rebranded with [`set_messages`](fn.set_messages.html).
+//!
+//! Short, single-line values are reported more compactly, with a caret under the first
+//! differing character:
+//! ```text
+//! expected: "foo bar"
+//! actual:   "foo baz"
+//!                  ^
+//! ```
+//!
+//! # Environment variables
+//! * `DIFF_ASSERT_SHOW_ACTUAL=1` - [`assert_diff!`](macro.assert_diff.html) and
+//!   [`try_diff!`](macro.try_diff.html) append a fenced copy of the whole actual text to the
+//!   report, ready to be copy-pasted as a new expected value. Compact reports already contain it.
+//! * `DIFF_ASSERT_SHOW_LITERAL=1` - the same, but the actual text is formatted as a Rust string
+//!   literal, see [`raw_string_literal`](fn.raw_string_literal.html).
+//! * `DIFF_ASSERT_IDE_BLOCK=1` - reports of [`assert_diff!`](macro.assert_diff.html) and
+//!   [`try_diff!`](macro.try_diff.html) start with the first differing pair of lines formatted
+//!   like a failed `assert_eq!`, so IDEs offer their diff viewer for it.
+//!
+//! # Features
+//! * `simd` - texts are split into lines with `memchr`, which is much faster on large inputs.
+//! * `unicode` - changed lines are highlighted along grapheme clusters from `unicode-segmentation`,
+//!   so flags, Indic scripts and Hangul are never split.
 
+mod as_lines;
+mod compact;
+mod defaults;
+mod error;
+mod hook;
+mod literal;
+mod messages;
+pub mod normalizers;
+mod options;
+
+pub use crate::as_lines::{AsLines, FixedWidth};
+pub use crate::defaults::{default_display_options, set_default_display_options};
+pub use crate::error::DiffError;
+pub use crate::hook::{clear_failure_hook, set_failure_hook, Failure};
+pub use crate::literal::raw_string_literal;
+pub use crate::messages::{messages, set_messages, English, Messages};
+pub use crate::options::DiffAssertOptions;
 pub use diff_utils::*;
-use std::str::Lines;
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 
 /// Asserts equality between [`Debug`](std::fmt::Debug) output of any two objects.
 /// Internally it uses `try_dbg!` and then panics if outputs are not equal.
@@ -62,7 +142,8 @@ use std::str::Lines;
 /// # Input
 /// `$expected` - Expected outcome. Has to implement [`Debug`](std::fmt::Debug) trait,
 /// `$actual` - Actual outcome. Has to implement [`Debug`](std::fmt::Debug) trait,
-/// `$message_args` - Optional message when assertion fails.
+/// `$message_args` - Optional message when assertion fails,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
 ///
 /// # Panics
 /// If expected != actual
@@ -81,25 +162,27 @@ use std::str::Lines;
 /// ```
 #[macro_export]
 macro_rules! assert_dbg {
-    ($expected: expr, $actual: expr) => {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
         $crate::assert_diff!(
             format!("{:#?}", $expected),
-            format!("{:#?}", $actual))
+            format!("{:#?}", $actual)
+            $(; $($opt $(= $val)?),*)?)
     };
-    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)*) => {
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
         $crate::assert_diff!(
             format!("{:#?}", $expected),
             format!("{:#?}", $actual),
-            $message $(,$message_args)*)
+            $message $(,$message_args)* $(; $($opt $(= $val)?),*)?)
     }
 }
 
-/// Checks equality between [`Debug`](std::fmt::Debug) output of any two objects and returns Err(String) if it fails.
+/// Checks equality between [`Debug`](std::fmt::Debug) output of any two objects and returns [`Err(DiffError)`](struct.DiffError.html) if it fails.
 ///
 /// # Input
 /// `$expected` - Expected outcome. Has to implement [`Debug`](std::fmt::Debug) trait,
 /// `$actual` - Actual outcome. Has to implement [`Debug`](std::fmt::Debug) trait,
-/// `$message_args` - Optional message when objects are not equal.
+/// `$message_args` - Optional message when objects are not equal,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
 ///
 /// # Errors
 /// When `$expected` != `$actual`
@@ -120,29 +203,28 @@ macro_rules! assert_dbg {
 /// ```
 #[macro_export]
 macro_rules! try_dbg {
-    ($expected: expr, $actual: expr) => {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
         $crate::try_diff!(
             format!("{:#?}", $expected),
-            format!("{:#?}", $actual))
+            format!("{:#?}", $actual)
+            $(; $($opt $(= $val)?),*)?)
     };
-    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)*) => {
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
         $crate::try_diff!(
             format!("{:#?}", $expected),
             format!("{:#?}", $actual),
-            $message $(,$message_args)*)
+            $message $(,$message_args)* $(; $($opt $(= $val)?),*)?)
     }
 }
 
-/// Checks equality between output of any two objects and returns Err(String) if it fails.
-/// This macro requires that arguments have method:
-/// ```ignore
-/// fn lines(&self) -> std::str::Lines;
-/// ```
+/// Checks equality between output of any two objects and returns [`Err(DiffError)`](struct.DiffError.html) if it fails.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
 ///
 /// # Input
 /// `$expected` - Expected outcome,
 /// `$actual` - Actual outcome,
-/// `$message_args` - Optional message when objects are not equal.
+/// `$message_args` - Optional message when objects are not equal,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
 ///
 /// # Errors
 /// When `$expected` != `$actual`
@@ -165,25 +247,26 @@ macro_rules! try_dbg {
 /// ```
 #[macro_export]
 macro_rules! try_diff {
-    ($expected: expr, $actual: expr) => {
-        $crate::try_diff!($expected, $actual, "Found differences")
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::try_diff!($expected, $actual, "{}", $crate::messages().found_differences() $(; $($opt $(= $val)?),*)?)
     };
-    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)*) => {
-        $crate::inner_try_diff($expected.lines(), $actual.lines(), format!($message, $($message_args),*))
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_try_diff(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options)
+        }
     };
 }
 
 /// Asserts equality between lines of any two objects.
 /// Internally it uses [`try_diff!`](macro.try_diff.html) and then panics if outputs are not equal.
-/// This macro requires that arguments have method:
-/// ```ignore
-/// fn lines(&self) -> std::str::Lines;
-/// ```
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
 ///
 /// # Input
 /// `$expected` - Expected outcome,
 /// `$actual` - Actual outcome,
-/// `$message_args` - Optional message when assertion fails.
+/// `$message_args` - Optional message when assertion fails,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
 ///
 /// # Panics
 /// If expected != actual
@@ -204,41 +287,725 @@ macro_rules! try_diff {
 /// ```
 #[macro_export]
 macro_rules! assert_diff {
-    ($expected: expr, $actual: expr) => {
-        $crate::assert_diff!($expected, $actual, "Found differences")
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::assert_diff!($expected, $actual, "{}", $crate::messages().found_differences() $(; $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_assert_diff(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options)
+        }
+    };
+}
+
+/// Checks that lines of `$expected` appear in `$actual` in the same order, but not necessarily
+/// contiguously, and returns [`Err(DiffError)`](struct.DiffError.html) if they don't.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Lines which have to be found,
+/// `$actual` - Actual outcome,
+/// `$message_args` - Optional message when some lines were not found,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Errors
+/// When any line of `$expected` cannot be found in `$actual` after the previously matched one.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = r#"started
+/// finished"#;
+///
+/// let actual = r#"started
+/// some noise
+/// finished"#;
+///
+/// assert!(try_subsequence!(expected, actual, "Here is an optional message").is_ok());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_subsequence {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::try_subsequence!($expected, $actual, "{}", $crate::messages().lines_not_found() $(; $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_try_subsequence(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options)
+        }
+    };
+}
+
+/// Asserts that lines of `$expected` appear in `$actual` in the same order, but not necessarily
+/// contiguously. Great for looking for key events in noisy logs.
+/// Internally it uses [`try_subsequence!`](macro.try_subsequence.html) and then panics if any line
+/// was not found.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Lines which have to be found,
+/// `$actual` - Actual outcome,
+/// `$message_args` - Optional message when assertion fails,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Panics
+/// When any line of `$expected` cannot be found in `$actual` after the previously matched one.
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = r#"started
+/// finished"#;
+///
+/// let actual = r#"finished
+/// started"#;
+///
+/// assert_subsequence!(expected, actual, "Here is an optional message");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_subsequence {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::assert_subsequence!($expected, $actual, "{}", $crate::messages().lines_not_found() $(; $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_assert_subsequence(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options)
+        }
+    };
+}
+
+/// Checks that both objects consist of the same lines, ignoring their order entirely, and returns
+/// [`Err(DiffError)`](struct.DiffError.html) if they don't. Both inputs are treated as multisets, so the number of occurrences
+/// of each line matters.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Expected outcome,
+/// `$actual` - Actual outcome,
+/// `$message_args` - Optional message when objects are not equal,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Errors
+/// When some lines are missing or extra, or occur a different number of times.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = r#"foo
+/// bar"#;
+///
+/// let actual = r#"bar
+/// foo"#;
+///
+/// assert!(try_same_lines!(expected, actual, "Here is an optional message").is_ok());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_same_lines {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::try_same_lines!($expected, $actual, "{}", $crate::messages().found_differences() $(; $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_try_same_lines(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options)
+        }
+    };
+}
+
+/// Asserts that both objects consist of the same lines, ignoring their order entirely.
+/// Useful for query results and event streams where ordering is nondeterministic.
+/// Internally it uses [`try_same_lines!`](macro.try_same_lines.html) and then panics if lines differ.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Expected outcome,
+/// `$actual` - Actual outcome,
+/// `$message_args` - Optional message when assertion fails,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Panics
+/// When some lines are missing or extra, or occur a different number of times.
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = r#"foo
+/// bar"#;
+///
+/// let actual = r#"bar
+/// bar"#;
+///
+/// assert_same_lines!(expected, actual, "Here is an optional message");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_same_lines {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::assert_same_lines!($expected, $actual, "{}", $crate::messages().found_differences() $(; $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_assert_same_lines(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options)
+        }
+    };
+}
+
+/// Checks equality between lines of any two objects regardless of their order, and returns
+/// [`Err(DiffError)`](struct.DiffError.html) if it fails. Both sides are sorted first, so the
+/// diff shows only genuinely missing and extra lines, e.g. for dumps of hash maps. It is the same
+/// as [`try_diff!`](macro.try_diff.html) with the `unordered` option.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Expected outcome,
+/// `$actual` - Actual outcome,
+/// `$message_args` - Optional message when objects are not equal,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Errors
+/// When some lines are missing or extra, or occur a different number of times.
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = "a: 1\nb: 2\nc: 3";
+/// let actual = "c: 3\na: 1\nb: 2";
+///
+/// assert!(try_diff_sorted!(expected, actual, "Here is an optional message").is_ok());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_diff_sorted {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::try_diff!($expected, $actual; unordered $(, $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::try_diff!($expected, $actual, $message $(,$message_args)*; unordered $(, $($opt $(= $val)?),*)?)
+    };
+}
+
+/// Asserts equality between lines of any two objects regardless of their order.
+/// Internally it uses [`try_diff_sorted!`](macro.try_diff_sorted.html) and then panics if lines
+/// differ. Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Expected outcome,
+/// `$actual` - Actual outcome,
+/// `$message_args` - Optional message when assertion fails,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Panics
+/// When some lines are missing or extra, or occur a different number of times.
+///
+/// # Examples
+///
+/// ```rust,should_panic
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = "a: 1\nb: 2\nc: 3";
+/// let actual = "c: 3\na: 1\nb: 4";
+///
+/// assert_diff_sorted!(expected, actual, "Here is an optional message");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_diff_sorted {
+    ($expected: expr, $actual: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::assert_diff!($expected, $actual; unordered $(, $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::assert_diff!($expected, $actual, $message $(,$message_args)*; unordered $(, $($opt $(= $val)?),*)?)
+    };
+}
+
+/// Checks that lines of two objects differ by no more than a budget, and returns
+/// [`Err(DiffError)`](struct.DiffError.html) with the whole diff if they differ more.
+/// Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Expected outcome,
+/// `$actual` - Actual outcome,
+/// `$budget` - Either `max_changed_lines = N` or `max_changed_percent = P`, see
+/// [`ChangeBudget`](enum.ChangeBudget.html) for how lines are counted,
+/// `$message_args` - Optional message when objects differ too much,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Errors
+/// When `$expected` and `$actual` differ by more lines than allowed
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = "Report\nfoo: 1\nbar: 2\ntook 12ms";
+/// let actual = "Report\nfoo: 1\nbar: 2\ntook 15ms";
+///
+/// assert!(try_diff_within!(expected, actual, max_changed_lines = 1).is_ok());
+/// assert!(try_diff_within!(expected, actual, max_changed_percent = 10).is_err());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! try_diff_within {
+    ($expected: expr, $actual: expr, $budget: ident = $max: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::try_diff_within!($expected, $actual, $budget = $max, "{}", $crate::messages().too_many_differences() $(; $($opt $(= $val)?),*)?)
     };
-    ($expected: expr, $actual: expr, $message: literal $(,$message_args: expr)*) => {
-        $crate::inner_assert_diff($expected.lines(), $actual.lines(), format!($message, $($message_args),*))
+    ($expected: expr, $actual: expr, $budget: ident = $max: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_try_diff_within(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options, $crate::__change_budget!($budget = $max))
+        }
     };
 }
 
+/// Asserts that lines of two objects differ by no more than a budget, e.g. for reports which
+/// include a small amount of legitimate variability.
+/// Internally it uses [`try_diff_within!`](macro.try_diff_within.html) and then panics if outputs
+/// differ too much. Arguments have to implement [`AsLines`](trait.AsLines.html).
+///
+/// # Input
+/// `$expected` - Expected outcome,
+/// `$actual` - Actual outcome,
+/// `$budget` - Either `max_changed_lines = N` or `max_changed_percent = P`, see
+/// [`ChangeBudget`](enum.ChangeBudget.html) for how lines are counted,
+/// `$message_args` - Optional message when assertion fails,
+/// `$options` - Optional [`DiffAssertOptions`](struct.DiffAssertOptions.html) after a semicolon, e.g. `; ignore_case, context = 8`.
+///
+/// # Panics
+/// If expected and actual differ by more lines than allowed
+///
+/// # Examples
+///
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// let expected = "Report\nfoo: 1\nbar: 2\ntook 12ms";
+/// let actual = "Report\nfoo: 1\nbar: 2\ntook 15ms";
+///
+/// assert_diff_within!(expected, actual, max_changed_lines = 1, "Report changed too much");
+/// # }
+/// ```
+#[macro_export]
+macro_rules! assert_diff_within {
+    ($expected: expr, $actual: expr, $budget: ident = $max: expr $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        $crate::assert_diff_within!($expected, $actual, $budget = $max, "{}", $crate::messages().too_many_differences() $(; $($opt $(= $val)?),*)?)
+    };
+    ($expected: expr, $actual: expr, $budget: ident = $max: expr, $message: literal $(,$message_args: expr)* $(; $($opt: ident $(= $val: expr)?),* $(,)?)?) => {
+        {
+            let options = $crate::__diff_assert_options!($($($opt $(= $val)?),*)?);
+            $crate::inner_assert_diff_within(options.lines(&$expected), options.lines(&$actual), format!($message, $($message_args),*), options, $crate::__change_budget!($budget = $max))
+        }
+    };
+}
+
+/// How much two objects may differ in [`try_diff_within!`](macro.try_diff_within.html).
+/// Every block of changed lines counts as many lines as the longer of its sides, so a line
+/// replaced by another one counts once, like a removed or an inserted one, and two lines replaced
+/// by three count three times.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub enum ChangeBudget {
+    /// `max_changed_lines = N`
+    Lines(usize),
+    /// `max_changed_percent = P`, relative to the number of lines of the longer side
+    Percent(f64),
+}
+
+/// Turns the budget of [`try_diff_within!`](macro.try_diff_within.html) into
+/// [`ChangeBudget`](enum.ChangeBudget.html).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __change_budget {
+    (max_changed_lines = $max: expr) => {
+        $crate::ChangeBudget::Lines($max)
+    };
+    (max_changed_percent = $max: expr) => {
+        $crate::ChangeBudget::Percent(f64::from($max))
+    };
+}
+
+/// Report of differences between `expected` and `actual`, the same as
+/// [`try_diff!`](macro.try_diff.html) gives, or `None` if they are equal. It neither panics nor
+/// returns an error, so it is meant for embedding reports in other assertion libraries and custom
+/// `assert!` wrappers. Failure hooks are not called, and the report is plain text, unless the
+/// [`colored`](struct.DiffAssertOptions.html#method.colored) option is set.
+///
+/// # Example
+/// ```rust
+/// use diff_assert::{diff_string, DiffAssertOptions};
+/// assert_eq!(diff_string("foo", "FOO", DiffAssertOptions::default().ignore_case()), None);
+///
+/// let report = diff_string("foo\nbar", "foo\nbaz", DiffAssertOptions::default()).unwrap();
+/// assert!(report.contains("Found differences"));
+/// ```
+pub fn diff_string(expected: &str, actual: &str, options: DiffAssertOptions) -> Option<String> {
+    let e = options.prepare_expected(options.lines(expected));
+    let a = options.prepare(options.lines(actual));
+    let e: Vec<&str> = e.iter().map(AsRef::as_ref).collect();
+    let a: Vec<&str> = a.iter().map(AsRef::as_ref).collect();
+    let comparison = comparison(&e, &a, &options);
+    if comparison.are_equal() {
+        return None;
+    }
+    let result = comparison.compare_infallible();
+    if result.is_empty() {
+        return None;
+    }
+    let msg_fmt = messages().found_differences();
+    let report = report(&comparison, &result, msg_fmt, &options, None);
+    Some(match options.colored {
+        true => report.to_colored_string(),
+        false => report.to_plain_string(),
+    })
+}
+
+#[doc(hidden)]
+pub fn inner_try_diff(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+) -> Result<(), DiffError> {
+    let e = options.prepare_expected(expected);
+    let a = options.prepare(actual);
+    let e: Vec<&str> = e.iter().map(AsRef::as_ref).collect();
+    let a: Vec<&str> = a.iter().map(AsRef::as_ref).collect();
+    try_diff_lines(&e, &a, &msg_fmt, &options)
+}
+
+/// Comparison of prepared lines, the same for every macro comparing whole texts.
+fn comparison<'a>(
+    e: &'a [&'a str],
+    a: &'a [&'a str],
+    options: &DiffAssertOptions,
+) -> Comparison<'a> {
+    Comparison {
+        context_radius: options.context_radius.unwrap_or(3),
+        unordered: options.unordered,
+        options: options.compare,
+        ..Comparison::new(e, a)
+    }
+}
+
+/// Compares prepared lines.
+fn try_diff_lines(
+    e: &[&str],
+    a: &[&str],
+    msg_fmt: &str,
+    options: &DiffAssertOptions,
+) -> Result<(), DiffError> {
+    let comparison = comparison(e, a, options);
+    if comparison.are_equal() {
+        return Ok(());
+    }
+    let result = comparison.compare_infallible();
+    if result.is_empty() {
+        return Ok(());
+    }
+    let error = report(&comparison, &result, msg_fmt, options, None);
+    hook::fail(options, e, a, Some(&result), error)
+}
+
+/// Reports the result of the failed comparison. `summary` is appended to the report, if there
+/// is one.
+fn report(
+    comparison: &Comparison,
+    result: &CompareResult,
+    msg_fmt: &str,
+    options: &DiffAssertOptions,
+    summary: Option<String>,
+) -> DiffError {
+    let (e, a) = (comparison.left, comparison.right);
+    let compact_lines = options.compact_lines.unwrap_or(1);
+    let compact = match options.unordered {
+        true => None,
+        false => compact::render(e, a, msg_fmt, compact_lines),
+    };
+    let mut message = match compact {
+        Some(message) => message,
+        None => {
+            let mut message = result
+                .display(default_display_options().msg_fmt(msg_fmt))
+                .to_string();
+            if options.show_actual || env_flag("DIFF_ASSERT_SHOW_ACTUAL") {
+                message.push_str(&actual_block(a));
+            }
+            message
+        }
+    };
+    if options.show_literal || env_flag("DIFF_ASSERT_SHOW_LITERAL") {
+        message.push_str(&format!(
+            "\n{}\n{}\n",
+            messages().actual_as_literal(),
+            raw_string_literal(&a.join("\n"))
+        ));
+    }
+    if options.ide_block || env_flag("DIFF_ASSERT_IDE_BLOCK") {
+        message.insert_str(0, &ide_block(comparison));
+    }
+    if let Some(summary) = summary {
+        message.push_str(&format!("\n{}\n", summary));
+    }
+    DiffError::new(message)
+}
+
+/// First differing pair of lines in the shape of the `assert_eq!` failure message.
+fn ide_block(comparison: &Comparison) -> String {
+    let (left, right) = comparison.first_difference().unwrap_or_default();
+    let side = |line: Option<Line>| match line {
+        Some(line) => format!("{:?}", line.inner()),
+        None => "<no line>".to_string(),
+    };
+    format!(
+        "assertion failed: `(left == right)`\n  left: `{}`,\n right: `{}`\n",
+        side(left),
+        side(right)
+    )
+}
+
+/// Checks if environment variable is set to anything but empty string or `0`.
+fn env_flag(name: &str) -> bool {
+    std::env::var_os(name).is_some_and(|v| !v.is_empty() && v != "0")
+}
+
+/// Fenced copy of the whole actual text. Fence is always longer than any backtick run inside.
+fn actual_block(actual: &[&str]) -> String {
+    let longest_run = actual
+        .iter()
+        .flat_map(|line| line.split(|c| c != '`'))
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_run.max(2) + 1);
+    let mut block = format!("\n{}\n{}text\n", messages().actual(), fence);
+    for line in actual {
+        block.push_str(line);
+        block.push('\n');
+    }
+    block.push_str(&fence);
+    block.push('\n');
+    block
+}
+
+#[doc(hidden)]
+pub fn inner_assert_diff(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+) {
+    if let Err(e) = inner_try_diff(expected, actual, msg_fmt, options) {
+        panic!("{}", e)
+    }
+}
+
+#[doc(hidden)]
+pub fn inner_try_diff_within(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+    budget: ChangeBudget,
+) -> Result<(), DiffError> {
+    let e = options.prepare_expected(expected);
+    let a = options.prepare(actual);
+    let e: Vec<&str> = e.iter().map(AsRef::as_ref).collect();
+    let a: Vec<&str> = a.iter().map(AsRef::as_ref).collect();
+
+    let comparison = comparison(&e, &a, &options);
+    if comparison.are_equal() {
+        return Ok(());
+    }
+    let result = comparison.compare_infallible();
+    let changed = changed_lines(&result);
+    let total = std::cmp::max(e.len(), a.len());
+    let allowed = match budget {
+        ChangeBudget::Lines(max) => changed <= max,
+        ChangeBudget::Percent(max) => changed as f64 <= max * total as f64 / 100.0,
+    };
+    if allowed {
+        return Ok(());
+    }
+    let summary = messages().changes_exceeded(changed, budget, total);
+    let error = report(&comparison, &result, &msg_fmt, &options, Some(summary));
+    hook::fail(&options, &e, &a, Some(&result), error)
+}
+
+/// Changed lines counted as described in [`ChangeBudget`](enum.ChangeBudget.html).
+fn changed_lines(result: &CompareResult) -> usize {
+    let mut changed = 0;
+    for hunk in result.hunks() {
+        let (mut removed, mut inserted) = (0, 0);
+        let kinds = hunk.lines().iter().map(|line| line.kind());
+        for kind in kinds.chain(Some(LineKind::Unchanged)) {
+            if kind.is_deletion() {
+                removed += 1;
+            } else if kind.is_insertion() {
+                inserted += 1;
+            } else {
+                changed += std::cmp::max(removed, inserted);
+                removed = 0;
+                inserted = 0;
+            }
+        }
+    }
+    changed
+}
+
+#[doc(hidden)]
+pub fn inner_assert_diff_within(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+    budget: ChangeBudget,
+) {
+    if let Err(e) = inner_try_diff_within(expected, actual, msg_fmt, options, budget) {
+        panic!("{}", e)
+    }
+}
+
+#[doc(hidden)]
+pub fn inner_try_subsequence(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+) -> Result<(), DiffError> {
+    let expected = options.prepare_expected(expected);
+    let a = options.prepare(actual);
+
+    let mut cursor = 0;
+    let mut broke_at = None;
+    let mut report = String::new();
+    for (i, line) in expected.iter().enumerate() {
+        match a[cursor..].iter().position(|a| a == line) {
+            Some(found) => {
+                cursor += found + 1;
+                report += &format!("{:03} {:03}   {}\n", i + 1, cursor, line);
+            }
+            None => {
+                broke_at.get_or_insert(cursor);
+                report += &format!("{:03}      -{}\n", i + 1, line);
+            }
+        }
+    }
+
+    match broke_at {
+        Some(broke_at) => {
+            let error = DiffError::new(format!(
+                "\n{}\n\n{}\n{}\n",
+                msg_fmt,
+                report,
+                messages().matching_broke_down(Some(broke_at).filter(|&line| line > 0))
+            ));
+            let e: Vec<&str> = expected.iter().map(AsRef::as_ref).collect();
+            let a: Vec<&str> = a.iter().map(AsRef::as_ref).collect();
+            hook::fail(&options, &e, &a, None, error)
+        }
+        None => Ok(()),
+    }
+}
+
 #[doc(hidden)]
-pub fn inner_try_diff(expected: Lines, actual: Lines, msg_fmt: String) -> Result<(), String> {
-    let e: Vec<&str> = expected.collect();
-    let a: Vec<&str> = actual.collect();
-    let result = Comparison::new(&e, &a).compare().unwrap();
-    if !result.is_empty() {
-        Err(result
-            .display(DisplayOptions {
-                offset: 0,
-                msg_fmt: &msg_fmt,
-            })
-            .to_string())
+pub fn inner_assert_subsequence(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+) {
+    if let Err(e) = inner_try_subsequence(expected, actual, msg_fmt, options) {
+        panic!("{}", e)
+    }
+}
+
+#[doc(hidden)]
+pub fn inner_try_same_lines(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+) -> Result<(), DiffError> {
+    let expected = options.prepare_expected(expected);
+    let actual = options.prepare(actual);
+    // Lines are reported in order of their first appearance, expected ones first.
+    let mut order: Vec<&str> = Vec::new();
+    let mut counts: HashMap<&str, (usize, usize)> = HashMap::new();
+    for (line, is_expected) in expected
+        .iter()
+        .map(|l| (l.as_ref(), true))
+        .chain(actual.iter().map(|l| (l.as_ref(), false)))
+    {
+        let count = counts.entry(line).or_insert_with(|| {
+            order.push(line);
+            (0, 0)
+        });
+        if is_expected {
+            count.0 += 1;
+        } else {
+            count.1 += 1;
+        }
+    }
+
+    let report = order
+        .into_iter()
+        .filter_map(|line| {
+            let (expected, actual) = counts[line];
+            let (sign, diff) = match expected.cmp(&actual) {
+                Ordering::Equal => return None,
+                Ordering::Greater => ("-", expected - actual),
+                Ordering::Less => ("+", actual - expected),
+            };
+            Some(format!(
+                "{} {}x {} {}\n",
+                sign,
+                diff,
+                line,
+                messages().line_counts(expected, actual)
+            ))
+        })
+        .collect::<String>();
+
+    if !report.is_empty() {
+        let error = DiffError::new(format!("\n{}\n\n{}", msg_fmt, report));
+        let e: Vec<&str> = expected.iter().map(AsRef::as_ref).collect();
+        let a: Vec<&str> = actual.iter().map(AsRef::as_ref).collect();
+        hook::fail(&options, &e, &a, None, error)
     } else {
         Ok(())
     }
 }
 
 #[doc(hidden)]
-pub fn inner_assert_diff(expected: Lines, actual: Lines, msg_fmt: String) {
-    if let Err(e) = inner_try_diff(expected, actual, msg_fmt) {
+pub fn inner_assert_same_lines(
+    expected: Vec<Cow<str>>,
+    actual: Vec<Cow<str>>,
+    msg_fmt: String,
+    options: DiffAssertOptions,
+) {
+    if let Err(e) = inner_try_same_lines(expected, actual, msg_fmt, options) {
         panic!("{}", e)
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
+    use crate::{DiffAssertOptions, FixedWidth, Newline};
 
     #[test]
     #[should_panic]
@@ -267,6 +1034,210 @@ mod tests {
         assert!(try_diff!(expected, actual).is_err());
     }
 
+    #[test]
+    fn show_actual_test() {
+        let err = super::try_diff_lines(
+            &["foo", "bar"],
+            &["foo", "```", "baz"],
+            "Found differences",
+            &DiffAssertOptions::default().show_actual(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_plain_string()
+            .ends_with("\nActual:\n````text\nfoo\n```\nbaz\n````\n"));
+    }
+
+    #[test]
+    fn ide_block_test() {
+        let err = super::try_diff_lines(
+            &["foo", "bar", "baz"],
+            &["foo", "BAR", "baz", "qux"],
+            "Found differences",
+            &DiffAssertOptions::default().ide_block(),
+        )
+        .unwrap_err();
+        assert!(err.to_plain_string().starts_with(
+            "assertion failed: `(left == right)`\n  left: `\"bar\"`,\n right: `\"BAR\"`\n\nFound differences\n\n... ..."
+        ));
+
+        let err = super::try_diff_lines(
+            &["foo"],
+            &["foo", "bar"],
+            "Found differences",
+            &DiffAssertOptions::default().ide_block(),
+        )
+        .unwrap_err();
+        assert!(err.to_plain_string().starts_with(
+            "assertion failed: `(left == right)`\n  left: `<no line>`,\n right: `\"bar\"`\n"
+        ));
+    }
+
+    #[test]
+    fn diff_string_test() {
+        use std::sync::atomic::{AtomicBool, Ordering};
+        use std::sync::Arc;
+
+        assert_eq!(
+            super::diff_string("foo\nbar", "foo\nbar", Default::default()),
+            None
+        );
+        let err = try_diff!("foo\nbar", "foo\nbaz").unwrap_err();
+        let report = super::diff_string("foo\nbar", "foo\nbaz", Default::default()).unwrap();
+        assert_eq!(report, err.to_plain_string());
+        let options = DiffAssertOptions::default().colored();
+        let report = super::diff_string("foo\nbar", "foo\nbaz", options).unwrap();
+        assert_eq!(report, err.to_colored_string());
+
+        let failed = Arc::new(AtomicBool::new(false));
+        let hook = failed.clone();
+        let options =
+            DiffAssertOptions::default().on_failure(move |_| hook.store(true, Ordering::SeqCst));
+        assert!(super::diff_string("foo", "bar", options).is_some());
+        assert!(!failed.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn compact_lines_test() {
+        let err = try_diff!("foo\nbar", "foo\nbaz"; compact_lines = 2).unwrap_err();
+        assert!(err
+            .to_plain_string()
+            .contains("expected: \"foo\\nbar\"\nactual:   \"foo\\nbaz\"\n"));
+        let err = try_diff!("foo\nbar", "foo\nbaz").unwrap_err();
+        assert!(err.to_plain_string().contains("... ...   @@ "));
+    }
+
+    #[test]
+    fn show_literal_test() {
+        let err = super::try_diff_lines(
+            &["foo"],
+            &["say \"hi\"", "bar"],
+            "Found differences",
+            &DiffAssertOptions::default().show_literal(),
+        )
+        .unwrap_err();
+        assert!(err
+            .to_plain_string()
+            .ends_with("\nActual as literal:\nr#\"say \"hi\"\nbar\"#\n"));
+    }
+
+    #[test]
+    fn as_lines_test() {
+        let owned = vec!["foo".to_string(), "bar".to_string()];
+        let borrowed = vec!["foo", "bar"];
+
+        assert_diff!("foo\nbar", owned);
+        assert_diff!(owned.as_slice(), &borrowed[..]);
+        assert_diff!(std::borrow::Cow::Borrowed("foo\nbar"), borrowed);
+        assert!(try_diff!(vec!["foo\nbar"], borrowed).is_err());
+    }
+
+    #[test]
+    fn bytes_test() {
+        let output: Vec<u8> = b"foo\nb\xffr\n".to_vec();
+
+        assert_diff!(b"foo\nb\xffr", output);
+        assert_diff!(
+            "foo\nb\u{FFFD}r",
+            std::ffi::OsString::from("foo\nb\u{FFFD}r")
+        );
+        let err = try_diff!("foo\nbar", output).unwrap_err();
+        assert!(err.to_plain_string().contains("invalid UTF-8"));
+    }
+
+    #[test]
+    fn fixed_width_test() {
+        let expected = FixedWidth("AAA1BBB2CCC3", 4);
+
+        assert_diff!(expected, "AAA1\nBBB2\nCCC3");
+        assert_diff!(expected, FixedWidth(b"AAA1BBB2CCC3".to_vec(), 4));
+        assert_diff!(FixedWidth("AAA1BB", 4), "AAA1\nBB");
+        let err = try_diff!(expected, FixedWidth("AAA1BXB2CCC3", 4)).unwrap_err();
+        assert!(err.to_plain_string().contains("BXB2"));
+    }
+
+    #[test]
+    fn newline_test() {
+        let expected = "a\r\nb\nc";
+
+        assert_diff!(expected, "a\nb\nc");
+        assert_diff!(expected, vec!["a", "b\nc"]; newline = Newline::CrLf);
+        assert_diff!(b"foo\0bar\0".to_vec(), "foo\0bar"; newline = Newline::Nul);
+        assert!(try_diff!("foo\0bar", "foo\nbar"; newline = Newline::Nul).is_err());
+        assert!(try_diff!(expected, "a\nb\nc"; newline = Newline::CrLf).is_err());
+        assert!(try_same_lines!("x;y", "y;x"; newline = Newline::Char(';')).is_ok());
+        assert!(try_diff!("a;b", "a;B"; newline = Newline::Char(';'), ignore_case).is_ok());
+    }
+
+    #[test]
+    fn expand_tabs_test() {
+        let expected = "id      name\n1       foo\n1234    bar";
+        let actual = "id\tname\n1\tfoo\n1234\tbar";
+
+        assert_diff!(expected, actual; expand_tabs = 8);
+        assert!(try_diff!(expected, actual; expand_tabs = 4).is_err());
+        assert!(try_diff!(expected, actual).is_err());
+        assert_diff!("a   b", "a\tb"; expand_tabs = 4, ignore_case);
+        assert_diff!("東京    x\ne\u{301}   y", "東京\tx\ne\u{301}\ty"; expand_tabs = 4);
+    }
+
+    #[test]
+    fn expand_env_test() {
+        let actual = format!("{}/Cargo.toml", env!("CARGO_MANIFEST_DIR"));
+
+        assert_diff!("${CARGO_MANIFEST_DIR}/Cargo.toml", actual; expand_env);
+        assert!(try_diff!("${CARGO_MANIFEST_DIR}/Cargo.toml", actual).is_err());
+        assert!(try_diff!(actual, "${CARGO_MANIFEST_DIR}/Cargo.toml"; expand_env).is_err());
+        assert!(try_subsequence!("${CARGO_PKG_NAME}", "diff_assert"; expand_env).is_ok());
+    }
+
+    #[test]
+    fn diff_within_test() {
+        let expected = "a\nb\nc\nd\ne";
+        let actual = "a\nB\nc\nd\ne";
+
+        assert_diff_within!(expected, actual, max_changed_lines = 1);
+        assert!(try_diff_within!(expected, actual, max_changed_lines = 0).is_err());
+        assert_diff_within!(expected, actual, max_changed_percent = 20, "Message {}", 1);
+        assert_diff_within!("a\nb\nc", "a\nB\nC\nD\nc", max_changed_lines = 3);
+        assert!(try_diff_within!("a\nb\nc", "a\nB\nC\nD\nc", max_changed_lines = 2).is_err());
+        assert_diff_within!(expected, actual, max_changed_lines = 0; ignore_case);
+        assert_diff_within!("b\na\nc", "a\nc\nb", max_changed_lines = 0; unordered);
+        let err = try_diff_within!(expected, actual, max_changed_percent = 19.5).unwrap_err();
+        assert!(err
+            .to_plain_string()
+            .ends_with("\n1 lines changed, at most 19.5% of 5 lines allowed\n"));
+        let err = try_diff_within!(expected, "a", max_changed_lines = 3, "Too much").unwrap_err();
+        assert!(err.to_plain_string().starts_with("\nToo much\n"));
+    }
+
+    #[test]
+    fn options_test() {
+        fn mask_ids(text: &str) -> String {
+            text.replace(|c: char| c.is_ascii_digit(), "0")
+        }
+
+        assert!(try_diff!("ID 17\nOK", "id 42\nok"; ignore_case, normalizer = mask_ids).is_ok());
+        assert!(try_diff!("ID 17", "id 42", "Message {}", 1; ignore_case,).is_err());
+        assert!(try_diff!(vec!["A\nB"], vec!["a", "b"]; ignore_case).is_err());
+        assert!(try_diff!(vec!["1", ""], vec!["2"]; normalizer = mask_ids).is_err());
+        assert!(try_subsequence!("FOO\nbar", "foo\nbaz\nBAR"; ignore_case).is_ok());
+        assert!(try_same_lines!("b\na", "A\nB"; ignore_case).is_ok());
+        assert!(try_dbg!("Foo", "foo"; ignore_case).is_ok());
+    }
+
+    #[test]
+    fn context_option_test() {
+        let expected = "a\nb\nc\nd\ne";
+        let actual = "a\nb\nC\nd\ne";
+
+        let err = try_diff!(expected, actual; context = 0).unwrap_err();
+        assert!(!err.to_plain_string().contains("   b"));
+        let err = try_diff!(expected, actual; context = 1).unwrap_err();
+        assert!(err.to_plain_string().contains("   b"));
+        assert!(!err.to_plain_string().contains("   a"));
+    }
+
     #[test]
     #[should_panic]
     fn dbg_test() {
@@ -274,4 +1245,106 @@ mod tests {
         let actual = ("Foo", "foo");
         assert_dbg!(expected, actual);
     }
+
+    #[test]
+    fn subsequence_test() {
+        let expected = "started\nloaded\nfinished";
+        let actual = "started\nnoise\nloaded\nmore noise\nfinished\n";
+
+        assert_subsequence!(expected, actual);
+    }
+
+    #[test]
+    fn subsequence_report_test() {
+        let expected = "started\nloaded\nfinished";
+        let actual = "started\nfinished\nloaded";
+
+        let err = try_subsequence!(expected, actual).unwrap_err();
+        assert_eq!(
+            err.to_plain_string(),
+            "\nExpected lines not found\n\n\
+             001 001   started\n\
+             002 003   loaded\n\
+             003      -finished\n\
+             \nMatching broke down after actual line 003\n"
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn subsequence_empty_actual_test() {
+        assert_subsequence!("foo", "");
+    }
+
+    #[test]
+    fn same_lines_test() {
+        let expected = "foo\nbar\nfoo";
+        let actual = "bar\nfoo\nfoo";
+
+        assert_same_lines!(expected, actual);
+    }
+
+    #[test]
+    fn same_lines_report_test() {
+        let expected = "foo\nbar\nfoo\nbaz";
+        let actual = "baz\nqux\nfoo\nbaz";
+
+        let err = try_same_lines!(expected, actual).unwrap_err();
+        assert_eq!(
+            err.to_plain_string(),
+            "\nFound differences\n\n\
+             - 1x foo (expected 2, found 1)\n\
+             - 1x bar (expected 1, found 0)\n\
+             + 1x baz (expected 1, found 2)\n\
+             + 1x qux (expected 0, found 1)\n"
+        );
+    }
+
+    #[test]
+    fn failure_hook_test() {
+        use std::sync::{Arc, Mutex};
+
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let hook = |seen: &Arc<Mutex<Vec<_>>>| {
+            let seen = seen.clone();
+            move |failure: &crate::Failure| {
+                let hunks = failure.result.map(|result| result.hunks().len());
+                seen.lock().unwrap().push((failure.actual.join("|"), hunks));
+            }
+        };
+        assert!(try_diff!("foo", "foo"; on_failure = hook(&seen)).is_ok());
+        let err = try_diff!("foo\nbar", "FOO\nbaz"; ignore_case, on_failure = hook(&seen));
+        assert!(err.unwrap_err().to_plain_string().contains("+baz"));
+        assert!(try_subsequence!("bar", "foo"; on_failure = hook(&seen)).is_err());
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![("foo|baz".to_string(), Some(1)), ("foo".to_string(), None)]
+        );
+    }
+
+    #[test]
+    fn whitespace_test() {
+        assert_diff!("foo  \nbar", "foo\nbar\t"; ignore_trailing_whitespace);
+        assert_diff!("  foo\nbar", "\tfoo\nbar"; ignore_leading_whitespace);
+        assert_diff!("a  b\nc", "a\tb\nc"; collapse_inner_whitespace);
+        assert!(try_diff!("foo \nbar", "foo\nbar").is_err());
+
+        let err = try_diff!("foo \nbar\nbaz", "foo\nbar\nqux"; ignore_trailing_whitespace)
+            .unwrap_err()
+            .to_plain_string();
+        assert!(err.contains("000 000   foo \n"));
+        assert!(err.contains("+qux"));
+    }
+
+    #[test]
+    fn diff_sorted_test() {
+        assert_diff_sorted!("b\na\nb", "a\nb\nb");
+        assert_diff_sorted!("B\na", "A\nb", "Custom message"; ignore_case);
+
+        let err = try_diff_sorted!("c\na\nb", "a\nd\nc").unwrap_err();
+        let report = err.to_plain_string();
+        assert!(report.contains("-b\n"));
+        assert!(report.contains("+d\n"));
+        assert!(!report.contains("-c\n"));
+    }
 }
@@ -0,0 +1,27 @@
+/// Formats `text` as a Rust string literal, ready to be pasted into the source code as
+/// an expected value.
+///
+/// Raw string literal is used whenever possible, with as many `#` as needed so none of `"` inside
+/// terminates it. Texts with carriage returns fall back to regular, escaped literal, because raw
+/// strings cannot contain them.
+///
+/// # Example
+/// ```rust
+/// # use diff_assert::raw_string_literal;
+/// assert_eq!(raw_string_literal("foo\nbar"), "r\"foo\nbar\"");
+/// assert_eq!(raw_string_literal("say \"hi\""), "r#\"say \"hi\"\"#");
+/// assert_eq!(raw_string_literal("\"#"), "r##\"\"#\"##");
+/// assert_eq!(raw_string_literal("foo\r\n"), "\"foo\\r\\n\"");
+/// ```
+pub fn raw_string_literal(text: &str) -> String {
+    if text.contains('\r') {
+        return format!("{:?}", text);
+    }
+    let hashes = text
+        .match_indices('"')
+        .map(|(i, _)| text[i + 1..].chars().take_while(|c| *c == '#').count() + 1)
+        .max()
+        .unwrap_or(0);
+    let hashes = "#".repeat(hashes);
+    format!("r{}\"{}\"{}", hashes, text, hashes)
+}
@@ -0,0 +1,106 @@
+use crate::ChangeBudget;
+use std::sync::RwLock;
+
+static MESSAGES: RwLock<Option<&'static dyn Messages>> = RwLock::new(None);
+
+/// Fixed fragments of failure reports. Every method has an English default, so an
+/// implementation overrides only what it needs to localize or rebrand.
+///
+/// Messages passed to macros explicitly are used as they are, and the compact report of short
+/// values, e.g. `expected: "foo"`, isn't translated, so its caret stays aligned.
+///
+/// # Example
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// use diff_assert::{set_messages, Messages};
+///
+/// struct Polish;
+///
+/// impl Messages for Polish {
+///     fn found_differences(&self) -> &str {
+///         "Znaleziono różnice"
+///     }
+/// }
+///
+/// # fn main() {
+/// set_messages(&Polish);
+/// let err = try_diff!("foo\nbar", "foo\nbaz").unwrap_err();
+/// assert!(err.to_string().starts_with("\nZnaleziono różnice\n"));
+/// # }
+/// ```
+pub trait Messages: Sync {
+    /// Default message of [`assert_diff!`](macro.assert_diff.html) and
+    /// [`assert_same_lines!`](macro.assert_same_lines.html)
+    fn found_differences(&self) -> &str {
+        "Found differences"
+    }
+
+    /// Default message of [`assert_subsequence!`](macro.assert_subsequence.html)
+    fn lines_not_found(&self) -> &str {
+        "Expected lines not found"
+    }
+
+    /// Default message of [`assert_diff_within!`](macro.assert_diff_within.html)
+    fn too_many_differences(&self) -> &str {
+        "Found too many differences"
+    }
+
+    /// Heading of the copy of actual text, see
+    /// [`DiffAssertOptions::show_actual`](struct.DiffAssertOptions.html#method.show_actual)
+    fn actual(&self) -> &str {
+        "Actual:"
+    }
+
+    /// Heading of the actual text as a literal, see
+    /// [`DiffAssertOptions::show_literal`](struct.DiffAssertOptions.html#method.show_literal)
+    fn actual_as_literal(&self) -> &str {
+        "Actual as literal:"
+    }
+
+    /// Where [`assert_subsequence!`](macro.assert_subsequence.html) stopped matching. `after` is
+    /// the number of the last matched actual line, or `None` if not even the first one matched.
+    fn matching_broke_down(&self, after: Option<usize>) -> String {
+        match after {
+            Some(line) => format!("Matching broke down after actual line {:03}", line),
+            None => "Matching broke down before the first actual line".to_string(),
+        }
+    }
+
+    /// How many times [`assert_same_lines!`](macro.assert_same_lines.html) expected and found a
+    /// line
+    fn line_counts(&self, expected: usize, found: usize) -> String {
+        format!("(expected {}, found {})", expected, found)
+    }
+
+    /// Summary of [`assert_diff_within!`](macro.assert_diff_within.html) with `changed` lines out
+    /// of `total` lines of the longer side
+    fn changes_exceeded(&self, changed: usize, budget: ChangeBudget, total: usize) -> String {
+        let limit = match budget {
+            ChangeBudget::Lines(max) => format!("{} lines", max),
+            ChangeBudget::Percent(max) => format!("{}% of {} lines", max, total),
+        };
+        format!("{} lines changed, at most {} allowed", changed, limit)
+    }
+}
+
+/// English messages, used unless changed by [`set_messages`](fn.set_messages.html).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct English;
+
+impl Messages for English {}
+
+/// Sets messages used by all the macros from now on, in every thread.
+pub fn set_messages(messages: &'static dyn Messages) {
+    *MESSAGES
+        .write()
+        .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(messages);
+}
+
+/// Messages used by all the macros. [`English`](struct.English.html) unless changed by
+/// [`set_messages`](fn.set_messages.html).
+pub fn messages() -> &'static dyn Messages {
+    MESSAGES
+        .read()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .unwrap_or(&English)
+}
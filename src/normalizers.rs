@@ -0,0 +1,403 @@
+//! Ready-made normalizers, to be passed to the `normalizer` option of all the macros:
+//! ```rust
+//! # #[macro_use] extern crate diff_assert;
+//! use diff_assert::normalizers;
+//!
+//! # fn main() {
+//! assert_diff!("Total: 1,234.5", "Total: 1 234,5"; normalizer = normalizers::locale_numbers);
+//! # }
+//! ```
+
+/// Characters which may separate groups of digits, or integer and fractional part.
+const NUMBER_SEPARATORS: &[char] = &[',', '.', ' ', '\'', '\u{a0}', '\u{202f}'];
+
+/// Unifies numbers written under different locales, e.g. `1,234.5`, `1.234,5`, `1 234,5` and
+/// `1'234.5` all become `1234.5`.
+///
+/// A separator is taken as a thousands one only if it splits the number into proper groups of
+/// three digits, so dates like `12.05.2020` or addresses like `10.0.0.1` stay untouched. A single
+/// group of three digits is ambiguous, and `1,234` as well as `1.234` become `1234`.
+pub fn locale_numbers(text: &str) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut normalized = String::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if !chars[i].is_ascii_digit() {
+            normalized.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        let mut groups = Vec::new();
+        let mut separators = Vec::new();
+        loop {
+            let start = i;
+            while i < chars.len() && chars[i].is_ascii_digit() {
+                i += 1;
+            }
+            groups.push(&chars[start..i]);
+            match (chars.get(i), chars.get(i + 1)) {
+                (Some(sep), Some(next))
+                    if NUMBER_SEPARATORS.contains(sep) && next.is_ascii_digit() =>
+                {
+                    separators.push(*sep);
+                    i += 1;
+                }
+                _ => break,
+            }
+        }
+        write_number(&mut normalized, &groups, &separators);
+    }
+    normalized
+}
+
+fn write_number(out: &mut String, groups: &[&[char]], separators: &[char]) {
+    let last = separators.len();
+    if is_grouped(groups, separators) {
+        groups.iter().for_each(|group| out.extend(group.iter()));
+    } else if last > 0
+        && matches!(separators[last - 1], '.' | ',')
+        && (last == 1
+            || separators[0] != separators[last - 1]
+                && is_grouped(&groups[..last], &separators[..last - 1]))
+    {
+        groups[..last]
+            .iter()
+            .for_each(|group| out.extend(group.iter()));
+        out.push('.');
+        out.extend(groups[last].iter());
+    } else {
+        for (i, group) in groups.iter().enumerate() {
+            if i > 0 {
+                out.push(separators[i - 1]);
+            }
+            out.extend(group.iter());
+        }
+    }
+}
+
+/// Whether separators are the same thousands separator, e.g. `12,345,678`.
+fn is_grouped(groups: &[&[char]], separators: &[char]) -> bool {
+    !separators.is_empty()
+        && separators.iter().all(|sep| *sep == separators[0])
+        && (1..=3).contains(&groups[0].len())
+        && groups[0][0] != '0'
+        && groups[1..].iter().all(|group| group.len() == 3)
+}
+
+/// Replaces machine-specific paths with stable placeholders: the temporary directory with
+/// `[TMP]`, and home directories like `/home/<user>`, `/Users/<user>` or `C:\Users\<user>` with
+/// `[HOME]`. Nearly every integration test of a command line tool needs it.
+///
+/// Rest of the path is left as it is, so `/home/alice/.config\app` becomes `[HOME]/.config\app`.
+pub fn scrub_paths(text: &str) -> String {
+    let mut text = text.to_owned();
+    let temp_dir = std::env::temp_dir();
+    let temp_dir = temp_dir.to_string_lossy();
+    let temp_dir = temp_dir.trim_end_matches(['/', '\\']);
+    if !temp_dir.is_empty() {
+        text = text.replace(temp_dir, "[TMP]");
+    }
+    for prefix in &[":\\Users\\", ":/Users/", "/home/", "/Users/"] {
+        text = replace_user_dirs(&text, prefix);
+    }
+    text
+}
+
+/// Replaces `prefix` followed by a user name with `[HOME]`. Drive letter precedes the prefix on
+/// Windows.
+fn replace_user_dirs(text: &str, prefix: &str) -> String {
+    let mut scrubbed = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(found) = rest.find(prefix) {
+        let mut start = found;
+        if prefix.starts_with(':') {
+            match rest[..found].chars().next_back() {
+                Some(drive) if drive.is_ascii_alphabetic() => start -= 1,
+                _ => {
+                    scrubbed.push_str(&rest[..found + prefix.len()]);
+                    rest = &rest[found + prefix.len()..];
+                    continue;
+                }
+            }
+        }
+        let user = &rest[found + prefix.len()..];
+        let user_len = user
+            .find(|c: char| c == '/' || c == '\\' || c.is_whitespace() || "\"'`:;,)]>".contains(c))
+            .unwrap_or(user.len());
+        scrubbed.push_str(&rest[..start]);
+        if user_len == 0 {
+            scrubbed.push_str(&rest[start..found + prefix.len()]);
+        } else {
+            scrubbed.push_str("[HOME]");
+        }
+        rest = &user[user_len..];
+    }
+    scrubbed.push_str(rest);
+    scrubbed
+}
+
+/// Expands `${NAME}` tokens with values of environment variables. Tokens of variables which are
+/// not set, or are not valid Unicode, are left as they are.
+pub fn env_vars(text: &str) -> String {
+    let mut expanded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(len) => start + len,
+            None => break,
+        };
+        expanded.push_str(&rest[..start]);
+        match std::env::var(&rest[start + 2..end]) {
+            Ok(value) => expanded.push_str(&value),
+            Err(_) => expanded.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+    expanded
+}
+
+/// Replaces semantic versions like `1.2.3`, `0.1.0-alpha.1` or `2.0.0+build.5` with `[VERSION]`,
+/// e.g. to keep the version of the crate out of fixtures.
+pub fn mask_versions(text: &str) -> String {
+    replace_tokens(text, version_len, |_| true, "[VERSION]")
+}
+
+/// Like [`mask_versions`](fn.mask_versions.html), but only versions between `min` (inclusive)
+/// and `max` (exclusive) are masked, so anything out of the range shows up in the diff.
+/// Pre-release and build metadata are not taken into account in the comparison.
+///
+/// # Panics
+/// If `min` or `max` is not a semantic version.
+///
+/// # Example
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// use diff_assert::normalizers;
+///
+/// # fn main() {
+/// let output = format!("mytool {}", "1.4.2");
+/// assert_diff!("mytool 1.0.0", output; normalizer = normalizers::versions_within("1.0.0", "2.0.0"));
+/// # }
+/// ```
+pub fn versions_within(min: &str, max: &str) -> impl Fn(&str) -> String + Send + Sync + 'static {
+    let parse = |version: &str| {
+        parse_version(version).unwrap_or_else(|| panic!("{:?} is not a semantic version", version))
+    };
+    let (min, max) = (parse(min), parse(max));
+    move |text| {
+        replace_tokens(
+            text,
+            version_len,
+            |version| (min..max).contains(&parse_version(version).unwrap_or_default()),
+            "[VERSION]",
+        )
+    }
+}
+
+/// Replaces ISO 8601 dates like `2020-06-27` with `[DATE]`. Time, if any, is left as it is.
+pub fn mask_dates(text: &str) -> String {
+    replace_tokens(text, date_len, |_| true, "[DATE]")
+}
+
+/// Like [`mask_dates`](fn.mask_dates.html), but only dates between `from` and `to`, both
+/// inclusive and written as `YYYY-MM-DD`, are masked.
+pub fn dates_within(from: &str, to: &str) -> impl Fn(&str) -> String + Send + Sync + 'static {
+    let (from, to) = (from.to_owned(), to.to_owned());
+    move |text| {
+        replace_tokens(
+            text,
+            date_len,
+            |date| (from.as_str()..=to.as_str()).contains(&date),
+            "[DATE]",
+        )
+    }
+}
+
+/// Replaces tokens found by `token_len` at word boundaries, if they are `accepted`.
+fn replace_tokens(
+    text: &str,
+    token_len: fn(&str) -> Option<usize>,
+    accepted: impl Fn(&str) -> bool,
+    placeholder: &str,
+) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut i = 0;
+    let mut boundary = true;
+    while let Some(c) = text[i..].chars().next() {
+        if boundary && c.is_ascii_digit() {
+            if let Some(len) = token_len(&text[i..]) {
+                if accepted(&text[i..i + len]) {
+                    replaced.push_str(placeholder);
+                } else {
+                    replaced.push_str(&text[i..i + len]);
+                }
+                i += len;
+                boundary = false;
+                continue;
+            }
+        }
+        // `v` prefix of versions, like in `v1.2.3`
+        boundary = !c.is_alphanumeric() && c != '.' && c != '_' || boundary && c == 'v';
+        replaced.push(c);
+        i += c.len_utf8();
+    }
+    replaced
+}
+
+fn digits_len(text: &str) -> usize {
+    text.bytes().take_while(u8::is_ascii_digit).count()
+}
+
+/// Ends where the next character can't continue a word or a number.
+fn ends_token(text: &str) -> bool {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some('.') => !chars.next().is_some_and(|c| c.is_ascii_alphanumeric()),
+        Some(c) => !c.is_alphanumeric() && c != '_',
+        None => true,
+    }
+}
+
+fn version_len(text: &str) -> Option<usize> {
+    let mut len = 0;
+    for part in 0..3 {
+        if part > 0 {
+            len += text[len..].strip_prefix('.').map(|_| 1)?;
+        }
+        match digits_len(&text[len..]) {
+            0 => return None,
+            digits => len += digits,
+        }
+    }
+    for prefix in &['-', '+'] {
+        if let Some(rest) = text[len..].strip_prefix(*prefix) {
+            let label = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '-')
+                .unwrap_or(rest.len());
+            let label = rest[..label].trim_end_matches('.').len();
+            if label > 0 {
+                len += 1 + label;
+            }
+        }
+    }
+    Some(len).filter(|len| ends_token(&text[*len..]))
+}
+
+fn parse_version(version: &str) -> Option<(u64, u64, u64)> {
+    let mut parts = version
+        .split(['-', '+'])
+        .next()?
+        .split('.')
+        .map(|part| part.parse().ok());
+    let version = (parts.next()??, parts.next()??, parts.next()??);
+    Some(version).filter(|_| parts.next().is_none())
+}
+
+fn date_len(text: &str) -> Option<usize> {
+    let bytes = text.as_bytes();
+    let shape = bytes.len() >= 10
+        && digits_len(text) == 4
+        && bytes[4] == b'-'
+        && digits_len(&text[5..]) == 2
+        && bytes[7] == b'-'
+        && digits_len(&text[8..]) == 2;
+    if !shape || !ends_token(&text[10..]) && !text[10..].starts_with('T') {
+        return None;
+    }
+    let month: u32 = text[5..7].parse().ok()?;
+    let day: u32 = text[8..10].parse().ok()?;
+    Some(10).filter(|_| (1..=12).contains(&month) && (1..=31).contains(&day))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbers() {
+        for text in &[
+            "1,234.5",
+            "1.234,5",
+            "1 234,5",
+            "1'234.5",
+            "1\u{a0}234,5",
+            "1234.5",
+        ] {
+            assert_eq!(locale_numbers(text), "1234.5", "{}", text);
+        }
+        assert_eq!(locale_numbers("12,345,678 and 0,5"), "12345678 and 0.5");
+        assert_eq!(locale_numbers("1,234"), "1234");
+        assert_eq!(locale_numbers("0.125 <1,5>"), "0.125 <1.5>");
+    }
+
+    #[test]
+    fn not_numbers() {
+        for text in &[
+            "12.05.2020",
+            "10.0.0.1",
+            "1,234,5",
+            "2020 3 items",
+            "a, b. 1, 2",
+            "1.",
+            "",
+        ] {
+            assert_eq!(locale_numbers(text), *text);
+        }
+    }
+
+    #[test]
+    fn paths() {
+        let temp = std::env::temp_dir().join("foo.txt");
+        assert_eq!(
+            scrub_paths(&format!("Wrote {}", temp.display())),
+            format!("Wrote [TMP]{}foo.txt", std::path::MAIN_SEPARATOR)
+        );
+        assert_eq!(
+            scrub_paths("/home/alice/x, \"/Users/bob\" C:\\Users\\eve\\AppData d:/Users/joe"),
+            "[HOME]/x, \"[HOME]\" [HOME]\\AppData [HOME]"
+        );
+        assert_eq!(
+            scrub_paths("/home/ and :\\Users\\x"),
+            "/home/ and :\\Users\\x"
+        );
+    }
+
+    #[test]
+    fn env() {
+        let dir = env!("CARGO_MANIFEST_DIR");
+        assert_eq!(env_vars("${CARGO_MANIFEST_DIR}/x"), format!("{}/x", dir));
+        assert_eq!(
+            env_vars("$HOME ${DIFF_ASSERT_NOT_SET} ${} ${CARGO_PKG_NAME}${"),
+            "$HOME ${DIFF_ASSERT_NOT_SET} ${} diff_assert${"
+        );
+    }
+
+    #[test]
+    fn versions() {
+        assert_eq!(
+            mask_versions(
+                "v1.2.3, 0.1.0-alpha.1 and 2.0.0+build.5. Not 1.2 nor 1.2.3.4 nor 11.22.33x"
+            ),
+            "v[VERSION], [VERSION] and [VERSION]. Not 1.2 nor 1.2.3.4 nor 11.22.33x"
+        );
+        let within = versions_within("1.0.0", "2.0.0");
+        assert_eq!(
+            within("1.0.0 1.10.0-rc.1 2.0.0 0.9.9"),
+            "[VERSION] [VERSION] 2.0.0 0.9.9"
+        );
+    }
+
+    #[test]
+    fn dates() {
+        assert_eq!(
+            mask_dates("2020-06-27T18:10:03, (2021-12-31) 2020-13-01 12020-01-01 2020-01-011"),
+            "[DATE]T18:10:03, ([DATE]) 2020-13-01 12020-01-01 2020-01-011"
+        );
+        let within = dates_within("2020-01-01", "2020-12-31");
+        assert_eq!(
+            within("2019-12-31 2020-01-01 2020-12-31"),
+            "2019-12-31 [DATE] [DATE]"
+        );
+    }
+}
@@ -0,0 +1,272 @@
+use crate::hook::FailureHook;
+use crate::{normalizers, AsLines, Failure};
+use diff_utils::{display_width, CompareOptions, Newline};
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::Arc;
+
+type Normalizer = Arc<dyn Fn(&str) -> String + Send + Sync>;
+
+/// Per-call options of all the macros, passed after a semicolon:
+/// ```rust
+/// # #[macro_use] extern crate diff_assert;
+/// # fn main() {
+/// fn no_digits(text: &str) -> String {
+///     text.replace(|c: char| c.is_ascii_digit(), "#")
+/// }
+///
+/// assert_diff!("Took 12ms\nOK", "took 93ms\nok", "Here is an optional message";
+///     ignore_case, context = 8, normalizer = no_digits);
+/// # }
+/// ```
+///
+/// Every option is a name of the builder method, and a value after `=` is passed as its argument.
+/// So the example above is the same as:
+/// ```rust
+/// # use diff_assert::DiffAssertOptions;
+/// # fn no_digits(text: &str) -> String { text.into() }
+/// DiffAssertOptions::default()
+///     .ignore_case()
+///     .context(8)
+///     .normalizer(no_digits);
+/// ```
+#[derive(Clone, Default)]
+pub struct DiffAssertOptions {
+    pub(crate) colored: bool,
+    pub(crate) compact_lines: Option<usize>,
+    pub(crate) compare: CompareOptions,
+    pub(crate) context_radius: Option<usize>,
+    pub(crate) expand_env: bool,
+    pub(crate) ide_block: bool,
+    pub(crate) ignore_case: bool,
+    pub(crate) newline: Newline,
+    pub(crate) normalizers: Vec<Normalizer>,
+    pub(crate) on_failure: Option<FailureHook>,
+    pub(crate) show_actual: bool,
+    pub(crate) show_literal: bool,
+    pub(crate) tab_width: Option<usize>,
+    pub(crate) unordered: bool,
+}
+
+impl DiffAssertOptions {
+    /// Keeps ANSI color codes in reports of [`diff_string`](fn.diff_string.html), which are
+    /// plain text by default. Reports of macros are always colored like the terminal allows.
+    pub fn colored(mut self) -> Self {
+        self.colored = true;
+        self
+    }
+
+    /// Reports values of at most `lines` lines each like `assert_eq!` does, with both of them
+    /// shown in full and a caret under the first difference, instead of hunks. Default value: 1
+    pub fn compact_lines(mut self, lines: usize) -> Self {
+        self.compact_lines = Some(lines);
+        self
+    }
+
+    /// Treats every run of whitespace inside of lines as a single space, see
+    /// [`CompareOptions`](struct.CompareOptions.html). Reports show lines as they are.
+    pub fn collapse_inner_whitespace(mut self) -> Self {
+        self.compare = self.compare.collapse_inner_whitespace();
+        self
+    }
+
+    /// How many unchanged lines surround every hunk. Default value: 3
+    pub fn context(mut self, radius: usize) -> Self {
+        self.context_radius = Some(radius);
+        self
+    }
+
+    /// Expands `${NAME}` tokens of the expected side with environment variables before comparison,
+    /// so e.g. fixtures with `${CARGO_MANIFEST_DIR}` stay portable across machines.
+    /// See [`normalizers::env_vars`](normalizers/fn.env_vars.html).
+    pub fn expand_env(mut self) -> Self {
+        self.expand_env = true;
+        self
+    }
+
+    /// Starts the report with the first differing pair of lines in the shape of a failed
+    /// `assert_eq!`, which IDEs detect to offer their own diff viewer, like
+    /// `DIFF_ASSERT_IDE_BLOCK=1` does. The full report follows below.
+    pub fn ide_block(mut self) -> Self {
+        self.ide_block = true;
+        self
+    }
+
+    /// Ignores whitespace at the beginning of lines, see
+    /// [`CompareOptions`](struct.CompareOptions.html). Reports show lines as they are.
+    pub fn ignore_leading_whitespace(mut self) -> Self {
+        self.compare = self.compare.ignore_leading_whitespace();
+        self
+    }
+
+    /// Ignores whitespace at the end of lines, see
+    /// [`CompareOptions`](struct.CompareOptions.html). Reports show lines as they are.
+    pub fn ignore_trailing_whitespace(mut self) -> Self {
+        self.compare = self.compare.ignore_trailing_whitespace();
+        self
+    }
+
+    /// Compares both sides after converting them to lowercase. Report shows lowercase lines too.
+    pub fn ignore_case(mut self) -> Self {
+        self.ignore_case = true;
+        self
+    }
+
+    /// What separates lines of strings, bytes and OS strings, e.g. `newline = Newline::Nul` for
+    /// the output of `find -print0`. Default: [`Newline::Lines`](enum.Newline.html#variant.Lines)
+    pub fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Transforms each line of both sides before comparison, e.g. to mask timestamps.
+    /// Normalizers are applied in order they are added, and report shows normalized lines.
+    /// Some common ones are in [`normalizers`](normalizers/index.html).
+    pub fn normalizer(
+        mut self,
+        normalizer: impl Fn(&str) -> String + Send + Sync + 'static,
+    ) -> Self {
+        self.normalizers.push(Arc::new(normalizer));
+        self
+    }
+
+    /// Calls `hook` if this comparison fails, after the global one set with
+    /// [`set_failure_hook`](fn.set_failure_hook.html).
+    pub fn on_failure(mut self, hook: impl Fn(&Failure) + Send + Sync + 'static) -> Self {
+        self.on_failure = Some(Arc::new(hook));
+        self
+    }
+
+    /// Appends a fenced copy of the whole actual text to the diff report, like
+    /// `DIFF_ASSERT_SHOW_ACTUAL=1` does.
+    pub fn show_actual(mut self) -> Self {
+        self.show_actual = true;
+        self
+    }
+
+    /// Appends the actual text formatted as a Rust string literal to the diff report, like
+    /// `DIFF_ASSERT_SHOW_LITERAL=1` does.
+    pub fn show_literal(mut self) -> Self {
+        self.show_literal = true;
+        self
+    }
+
+    /// Expands tabs to spaces up to the next multiple of `width` columns, so column-aligned text
+    /// is compared and reported the same way regardless of how it was indented. Columns are
+    /// counted on the terminal, so wide characters take two of them and combining marks none.
+    pub fn expand_tabs(mut self, width: usize) -> Self {
+        self.tab_width = Some(width);
+        self
+    }
+
+    /// Compares lines regardless of their order, see
+    /// [`try_diff_sorted!`](macro.try_diff_sorted.html).
+    pub fn unordered(mut self) -> Self {
+        self.unordered = true;
+        self
+    }
+
+    /// The same as [`prepare`](#method.prepare), but expands environment variables first if
+    /// requested.
+    pub(crate) fn prepare_expected<'a>(&self, lines: Vec<Cow<'a, str>>) -> Vec<Cow<'a, str>> {
+        if !self.expand_env {
+            return self.prepare(lines);
+        }
+        let lines = lines
+            .into_iter()
+            .map(|line| match line.contains("${") {
+                true => Cow::Owned(normalizers::env_vars(&line)),
+                false => line,
+            })
+            .collect();
+        self.prepare(lines)
+    }
+
+    /// Applies normalizers, case folding and tab expansion. Lines are borrowed if there is
+    /// nothing to do.
+    pub(crate) fn prepare<'a>(&self, lines: Vec<Cow<'a, str>>) -> Vec<Cow<'a, str>> {
+        let lines = self.normalize(lines);
+        match self.tab_width {
+            Some(width) => lines
+                .into_iter()
+                .map(|line| match line.contains('\t') {
+                    true => Cow::Owned(expand_tabs(&line, width)),
+                    false => line,
+                })
+                .collect(),
+            None => lines,
+        }
+    }
+
+    /// Normalizes every line on its own, so lines keep their boundaries even if they contain the
+    /// separator or are empty.
+    fn normalize<'a>(&self, lines: Vec<Cow<'a, str>>) -> Vec<Cow<'a, str>> {
+        if self.normalizers.is_empty() && !self.ignore_case {
+            return lines;
+        }
+        lines
+            .into_iter()
+            .map(|line| {
+                let mut line = line.into_owned();
+                for normalizer in &self.normalizers {
+                    line = normalizer(&line);
+                }
+                if self.ignore_case {
+                    line = line.to_lowercase();
+                }
+                Cow::Owned(line)
+            })
+            .collect()
+    }
+
+    /// Splits macro arguments into lines with the configured separator.
+    #[doc(hidden)]
+    pub fn lines<'a, T: AsLines + ?Sized>(&self, value: &'a T) -> Vec<Cow<'a, str>> {
+        value.as_lines_with(self.newline)
+    }
+}
+
+impl fmt::Debug for DiffAssertOptions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DiffAssertOptions")
+            .field("colored", &self.colored)
+            .field("compact_lines", &self.compact_lines)
+            .field("compare", &self.compare)
+            .field("context_radius", &self.context_radius)
+            .field("expand_env", &self.expand_env)
+            .field("ide_block", &self.ide_block)
+            .field("ignore_case", &self.ignore_case)
+            .field("newline", &self.newline)
+            .field("normalizers", &self.normalizers.len())
+            .field("on_failure", &self.on_failure.is_some())
+            .field("show_actual", &self.show_actual)
+            .field("show_literal", &self.show_literal)
+            .field("tab_width", &self.tab_width)
+            .field("unordered", &self.unordered)
+            .finish()
+    }
+}
+
+fn expand_tabs(line: &str, width: usize) -> String {
+    let mut expanded = String::with_capacity(line.len());
+    let mut column = 0;
+    for (idx, segment) in line.split('\t').enumerate() {
+        if idx > 0 {
+            let spaces = width - column % width.max(1);
+            expanded += &" ".repeat(spaces);
+            column += spaces;
+        }
+        expanded += segment;
+        column += display_width(segment);
+    }
+    expanded
+}
+
+/// Turns trailing options of macros into [`DiffAssertOptions`](struct.DiffAssertOptions.html).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __diff_assert_options {
+    ($($opt: ident $(= $val: expr)?),*) => {
+        $crate::DiffAssertOptions::default() $(.$opt($($val)?))*
+    };
+}